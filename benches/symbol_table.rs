@@ -0,0 +1,89 @@
+// Compares the lookups of `symbol_table!()` with those of `tree_sitter::Language`
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tree_sitter_c_proc::symbol_table;
+use tree_sitter_c_proc_runtime::SymbolTable;
+
+static C_SYMBOLS: SymbolTable = symbol_table!();
+
+/// Names from the start, middle and end of the symbol table, and one that
+/// isn't in it.
+const NAMES: [(&str, bool); 5] = [
+    ("identifier", true),
+    ("if", false),
+    ("function_definition", true),
+    ("translation_unit", true),
+    ("function_defintion", true),
+];
+
+fn id_for_node_kind(c: &mut Criterion) {
+    let language = tree_sitter_c::language();
+    let mut group = c.benchmark_group("id_for_node_kind");
+
+    group.bench_function("Language", |b| {
+        b.iter(|| {
+            for (name, named) in NAMES {
+                black_box(language.id_for_node_kind(black_box(name), named));
+            }
+        })
+    });
+    group.bench_function("SymbolTable", |b| {
+        b.iter(|| {
+            for (name, named) in NAMES {
+                black_box(C_SYMBOLS.id_for_node_kind(black_box(name), named));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn field_id_for_name(c: &mut Criterion) {
+    let language = tree_sitter_c::language();
+    let names = ["argument", "body", "declarator", "value", "captures"];
+    let mut group = c.benchmark_group("field_id_for_name");
+
+    group.bench_function("Language", |b| {
+        b.iter(|| {
+            for name in names {
+                black_box(language.field_id_for_name(black_box(name)));
+            }
+        })
+    });
+    group.bench_function("SymbolTable", |b| {
+        b.iter(|| {
+            for name in names {
+                black_box(C_SYMBOLS.field_id_for_name(black_box(name)));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn node_kind_for_id(c: &mut Criterion) {
+    let language = tree_sitter_c::language();
+    let count = language.node_kind_count() as u16;
+    let mut group = c.benchmark_group("node_kind_for_id");
+
+    group.bench_function("Language", |b| {
+        b.iter(|| {
+            for id in 0..count {
+                black_box(language.node_kind_for_id(black_box(id)));
+            }
+        })
+    });
+    group.bench_function("SymbolTable", |b| {
+        b.iter(|| {
+            for id in 0..count {
+                black_box(C_SYMBOLS.node_kind_for_id(black_box(id)));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    id_for_node_kind,
+    field_id_for_name,
+    node_kind_for_id
+);
+criterion_main!(benches);
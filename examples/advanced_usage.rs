@@ -73,6 +73,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     {
         use std::collections::HashMap;
         use tree_sitter_c_proc::{field, kind, kw};
+        use tree_sitter_c_proc_runtime::declarator;
+        use tree_sitter_c_proc_runtime::traversal::descendants_of_kind;
 
         println!("Advanced usage of tree-sitter-c-proc macros\n");
 
@@ -91,7 +93,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         ) {
             if node.kind_id() == struct_specifier_id {
                 // Get the name of the struct if it has one
-                let name_field = field!("name");
+                let name_field = field!("struct_specifier", "name");
                 let name = if let Some(name_node) = node.child_by_field_id(name_field.get()) {
                     name_node.utf8_text(code.as_bytes()).unwrap().to_string()
                 } else {
@@ -101,7 +103,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 println!("Found struct: {}", name);
 
                 // Get the body field to find field declarations
-                let body_field = field!("body");
+                let body_field = field!("struct_specifier", "body");
                 if let Some(body) = node.child_by_field_id(body_field.get()) {
                     // Iterate through the body to find field declarations
                     let mut field_cursor = body.walk();
@@ -110,8 +112,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             let field_node = field_cursor.node();
                             if field_node.kind_id() == field_declaration_id {
                                 // Get the type and declarator
-                                let type_field = field!("type");
-                                let declarator_field = field!("declarator");
+                                let type_field = field!("field_declaration", "type");
+                                let declarator_field = field!("field_declaration", "declarator");
 
                                 if let Some(type_node) =
                                     field_node.child_by_field_id(type_field.get())
@@ -119,10 +121,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                     if let Some(declarator) =
                                         field_node.child_by_field_id(declarator_field.get())
                                     {
-                                        // Get the name of the field
-                                        let name_field = field!("name");
+                                        // The name is nested in pointer, array
+                                        // and function declarators
                                         if let Some(name_node) =
-                                            declarator.child_by_field_id(name_field.get())
+                                            declarator::resolve(declarator).name
                                         {
                                             println!(
                                                 "  Field: {} of type {}",
@@ -169,61 +171,40 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         // Example 2: Analyze function calls and their arguments
         println!("\nExample 2: Analyzing function calls and their arguments");
-        let call_expression_id = kind!("call_expression");
-        let mut cursor = tree.walk();
-
-        fn analyze_function_calls(
-            node: &tree_sitter::Node,
-            call_expression_id: u16,
-            code: &str,
-            cursor: &mut TreeCursor,
-        ) {
-            if node.kind_id() == call_expression_id {
-                // Get the function name
-                let function_field = field!("function");
-                if let Some(function) = node.child_by_field_id(function_field.get()) {
-                    let function_name = function.utf8_text(code.as_bytes()).unwrap();
-
-                    // Get the arguments
-                    let arguments_field = field!("arguments");
-                    if let Some(arguments) = node.child_by_field_id(arguments_field.get()) {
-                        let mut arg_count = 0;
-                        let mut arg_cursor = arguments.walk();
-
-                        if arg_cursor.goto_first_child() {
-                            loop {
-                                // Skip commas and other non-argument nodes
-                                if arg_cursor.node().kind() != "," {
-                                    arg_count += 1;
-                                }
+        // Walk the tree with a cursor instead of recursing
+        for node in descendants_of_kind(root_node, kind!("call_expression")) {
+            // Get the function name
+            let function_field = field!("call_expression", "function");
+            if let Some(function) = node.child_by_field_id(function_field.get()) {
+                let function_name = function.utf8_text(code.as_bytes()).unwrap();
+
+                // Get the arguments
+                let arguments_field = field!("call_expression", "arguments");
+                if let Some(arguments) = node.child_by_field_id(arguments_field.get()) {
+                    let mut arg_count = 0;
+                    let mut arg_cursor = arguments.walk();
+
+                    if arg_cursor.goto_first_child() {
+                        loop {
+                            // Skip commas and other non-argument nodes
+                            if arg_cursor.node().kind() != "," {
+                                arg_count += 1;
+                            }
 
-                                if !arg_cursor.goto_next_sibling() {
-                                    break;
-                                }
+                            if !arg_cursor.goto_next_sibling() {
+                                break;
                             }
                         }
-
-                        println!(
-                            "Found call to function '{}' with {} argument(s)",
-                            function_name, arg_count
-                        );
                     }
-                }
-            }
 
-            if cursor.goto_first_child() {
-                loop {
-                    analyze_function_calls(&cursor.node(), call_expression_id, code, cursor);
-                    if !cursor.goto_next_sibling() {
-                        break;
-                    }
+                    println!(
+                        "Found call to function '{}' with {} argument(s)",
+                        function_name, arg_count
+                    );
                 }
-                cursor.goto_parent();
             }
         }
 
-        analyze_function_calls(&root_node, call_expression_id, &code, &mut cursor);
-
         // Example 3: Find all variable declarations and their types
         println!("\nExample 3: Finding variable declarations and their types");
         let declaration_id = kind!("declaration");
@@ -241,16 +222,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         ) {
             if node.kind_id() == declaration_id {
                 // Get the type
-                let type_field = field!("type");
+                let type_field = field!("declaration", "type");
                 if let Some(type_node) = node.child_by_field_id(type_field.get()) {
                     let type_str = type_node.utf8_text(code.as_bytes()).unwrap().to_string();
 
                     // Get the declarator
-                    let declarator_field = field!("declarator");
+                    let declarator_field = field!("declaration", "declarator");
                     if let Some(declarator) = node.child_by_field_id(declarator_field.get()) {
-                        // Get the name of the variable
-                        let name_field = field!("name");
-                        if let Some(name_node) = declarator.child_by_field_id(name_field.get()) {
+                        // The name is nested in init, pointer, array and
+                        // function declarators
+                        if let Some(name_node) = declarator::resolve(declarator).name {
                             let var_name =
                                 name_node.utf8_text(code.as_bytes()).unwrap().to_string();
                             println!(
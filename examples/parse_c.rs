@@ -39,121 +39,104 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Demonstrate using the proc macros
     #[cfg(feature = "proc_macros")]
     {
-        use tree_sitter_c_proc::{field, kind, kw};
+        use tree_sitter::Node;
+        use tree_sitter_c_proc::{field, kw, visitor};
+        use tree_sitter_c_proc_runtime::declarator;
+
+        visitor!();
 
         println!("\nDemonstrating proc macros:");
 
-        // Example 1: Find all function definitions using kind! macro
-        println!("\nExample 1: Finding function definitions using kind! macro");
-        let function_def_id = kind!("function_definition");
-        let mut cursor = tree.walk();
-
-        fn visit_function_nodes(
-            node: &tree_sitter::Node,
-            function_def_id: u16,
-            code: &str,
-            cursor: &mut tree_sitter::TreeCursor,
-        ) {
-            if node.kind_id() == function_def_id {
+        // Example 1: Find all function definitions using the generated visitor
+        println!("\nExample 1: Finding function definitions using visitor! macro");
+
+        struct FunctionFinder<'a> {
+            code: &'a str,
+        }
+
+        impl<'tree> CVisitor<'tree> for FunctionFinder<'_> {
+            // Called for every symbol ID of function_definition
+            fn visit_function_definition(&mut self, node: Node<'tree>) -> VisitControl {
                 // Get the declarator field using field! macro
-                let declarator_field = field!("declarator");
+                let declarator_field = field!("function_definition", "declarator");
                 if let Some(declarator) = node.child_by_field_id(declarator_field.get()) {
-                    // Look for the identifier (name) field
-                    let name_field = field!("name");
-                    if let Some(name_node) = declarator.child_by_field_id(name_field.get()) {
+                    // The name is nested in the declarator, below a pointer
+                    // declarator for `Point* create_point(...)`
+                    if let Some(name_node) = declarator::resolve(declarator).name {
                         println!(
                             "Found function: {}",
-                            name_node.utf8_text(code.as_bytes()).unwrap()
+                            name_node.utf8_text(self.code.as_bytes()).unwrap()
                         );
                     }
                 }
-            }
-
-            if cursor.goto_first_child() {
-                loop {
-                    visit_function_nodes(&cursor.node(), function_def_id, code, cursor);
-                    if !cursor.goto_next_sibling() {
-                        break;
-                    }
-                }
-                cursor.goto_parent();
+                VisitControl::Continue
             }
         }
 
-        visit_function_nodes(&root_node, function_def_id, &code, &mut cursor);
+        walk(&tree, &mut FunctionFinder { code: &code });
 
         // Example 2: Find all if statements using kw! macro
         println!("\nExample 2: Finding if statements using kw! macro");
-        let if_keyword_id = kw!("if");
-        let mut cursor = tree.walk();
-
-        fn visit_if_nodes(
-            node: &tree_sitter::Node,
-            if_keyword_id: u16,
-            code: &str,
-            cursor: &mut tree_sitter::TreeCursor,
-        ) {
-            // Check if this is an if statement by its kind
-            if node.kind() == "if_statement" {
+
+        struct IfFinder<'a> {
+            code: &'a str,
+            if_keywords: usize,
+        }
+
+        impl<'tree> CVisitor<'tree> for IfFinder<'_> {
+            // The pre hook sees anonymous tokens as well as named nodes
+            fn enter(&mut self, node: Node<'tree>) -> VisitControl {
+                if node.kind_id() == kw!("if") {
+                    self.if_keywords += 1;
+                }
+                VisitControl::Continue
+            }
+
+            fn visit_if_statement(&mut self, node: Node<'tree>) -> VisitControl {
                 // Get the condition field using field! macro
-                let condition_field = field!("condition");
+                let condition_field = field!("if_statement", "condition");
                 if let Some(condition) = node.child_by_field_id(condition_field.get()) {
                     println!(
                         "Found if statement with condition: {}",
-                        condition.utf8_text(code.as_bytes()).unwrap()
+                        condition.utf8_text(self.code.as_bytes()).unwrap()
                     );
                 }
-            }
-
-            if cursor.goto_first_child() {
-                loop {
-                    visit_if_nodes(&cursor.node(), if_keyword_id, code, cursor);
-                    if !cursor.goto_next_sibling() {
-                        break;
-                    }
-                }
-                cursor.goto_parent();
+                VisitControl::Continue
             }
         }
 
-        visit_if_nodes(&root_node, if_keyword_id, &code, &mut cursor);
+        let mut if_finder = IfFinder {
+            code: &code,
+            if_keywords: 0,
+        };
+        walk(&tree, &mut if_finder);
+        println!("Found {} `if` keywords", if_finder.if_keywords);
 
         // Example 3: Find all return statements and their values
         println!("\nExample 3: Finding return statements and their values");
-        let return_keyword_id = kw!("return");
-        let mut cursor = tree.walk();
-
-        fn visit_return_nodes(
-            node: &tree_sitter::Node,
-            return_keyword_id: u16,
-            code: &str,
-            cursor: &mut tree_sitter::TreeCursor,
-        ) {
-            if node.kind() == "return_statement" {
-                // Get the value field using field! macro
-                let value_field = field!("value");
-                if let Some(value) = node.child_by_field_id(value_field.get()) {
+
+        struct ReturnFinder<'a> {
+            code: &'a str,
+        }
+
+        impl<'tree> CVisitor<'tree> for ReturnFinder<'_> {
+            fn visit_return_statement(&mut self, node: Node<'tree>) -> VisitControl {
+                // A return statement has no fields; its value is its only
+                // named child
+                if let Some(value) = node.named_child(0) {
                     println!(
                         "Found return statement with value: {}",
-                        value.utf8_text(code.as_bytes()).unwrap()
+                        value.utf8_text(self.code.as_bytes()).unwrap()
                     );
                 } else {
                     println!("Found return statement without value");
                 }
-            }
-
-            if cursor.goto_first_child() {
-                loop {
-                    visit_return_nodes(&cursor.node(), return_keyword_id, code, cursor);
-                    if !cursor.goto_next_sibling() {
-                        break;
-                    }
-                }
-                cursor.goto_parent();
+                // Nothing of interest below a return statement
+                VisitControl::SkipChildren
             }
         }
 
-        visit_return_nodes(&root_node, return_keyword_id, &code, &mut cursor);
+        walk(&tree, &mut ReturnFinder { code: &code });
     }
 
     Ok(())
@@ -0,0 +1,147 @@
+//! Configuration read from the manifest of the crate that invokes the macros.
+//!
+//! A crate selects the grammar its macros use by default in the
+//! `[package.metadata.tree-sitter-proc]` table of its `Cargo.toml`:
+//!
+//! ```toml
+//! [package.metadata.tree-sitter-proc]
+//! language = "cpp"
+//! node-types = "vendor/tree-sitter-cpp/src/node-types.json"
+//! ```
+//!
+//! `language` names a grammar of the registry, and `node-types` replaces the
+//! `node-types.json` bundled with its crate, relative to the manifest. Node
+//! kind and field IDs still come from the grammar's crate, since
+//! `node-types.json` doesn't list them.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use tree_sitter_proc_core::node_types::NodeTypes;
+
+use crate::grammar::{self, Grammar};
+
+/// The parts of a manifest the macros read.
+#[derive(Deserialize)]
+struct Manifest {
+    package: Option<Package>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    metadata: Option<Metadata>,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    #[serde(rename = "tree-sitter-proc")]
+    tree_sitter_proc: Option<Config>,
+}
+
+/// The `[package.metadata.tree-sitter-proc]` table.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct Config {
+    /// The name of the default grammar, such as `cpp`.
+    language: Option<String>,
+    /// The path of a `node-types.json` file, relative to the manifest.
+    node_types: Option<PathBuf>,
+}
+
+/// A manifest that was read before, and the default grammar it selects.
+struct Loaded {
+    path: PathBuf,
+    contents: String,
+    grammar: Result<&'static Grammar, String>,
+}
+
+/// Manifests are cached by their contents, because a long-lived process such
+/// as rust-analyzer expands the macros of several crates, and of the same
+/// crate after its manifest changed.
+static LOADED: Mutex<Vec<Loaded>> = Mutex::new(Vec::new());
+
+/// Returns the grammar the calling crate's manifest selects, or the built-in
+/// default if it doesn't configure one.
+///
+/// Returns an error message if the manifest can't be read or its
+/// `[package.metadata.tree-sitter-proc]` table is invalid.
+pub(crate) fn default_grammar() -> Result<&'static Grammar, String> {
+    let Some(path) = manifest_path() else {
+        return grammar::builtin_default();
+    };
+    let contents = fs::read_to_string(&path)
+        .map_err(|error| format!("failed to read {}: {error}", path.display()))?;
+
+    let mut loaded = LOADED.lock().unwrap_or_else(|error| error.into_inner());
+    if let Some(previous) = loaded
+        .iter()
+        .find(|previous| previous.path == path && previous.contents == contents)
+    {
+        return previous.grammar.clone();
+    }
+
+    let grammar = configured_grammar(&path, &contents)
+        .map_err(|message| {
+            format!(
+                "invalid [package.metadata.tree-sitter-proc] in {}: {message}",
+                path.display()
+            )
+        })
+        .and_then(|grammar| grammar.map_or_else(grammar::builtin_default, Ok));
+    loaded.push(Loaded {
+        path,
+        contents,
+        grammar: grammar.clone(),
+    });
+    grammar
+}
+
+/// Returns the path of the calling crate's manifest.
+///
+/// `CARGO_MANIFEST_DIR` is only missing when rustc runs outside of cargo.
+pub(crate) fn manifest_path() -> Option<PathBuf> {
+    env::var_os("CARGO_MANIFEST_DIR").map(|directory| Path::new(&directory).join("Cargo.toml"))
+}
+
+/// Returns the grammar selected by the manifest at `path`, or `None` if it has
+/// no `[package.metadata.tree-sitter-proc]` table.
+fn configured_grammar(path: &Path, contents: &str) -> Result<Option<&'static Grammar>, String> {
+    let manifest: Manifest = toml::from_str(contents).map_err(|error| error.to_string())?;
+    let Some(config) = manifest
+        .package
+        .and_then(|package| package.metadata)
+        .and_then(|metadata| metadata.tree_sitter_proc)
+    else {
+        return Ok(None);
+    };
+
+    let grammar = match &config.language {
+        Some(language) => grammar::find(language)?,
+        None => grammar::builtin_default()?,
+    };
+    let Some(node_types_path) = config.node_types else {
+        return Ok(Some(grammar));
+    };
+
+    // The path is made absolute for the `include_bytes!` that tracks it
+    let node_types_path = path
+        .parent()
+        .expect("manifest paths have a parent")
+        .join(node_types_path);
+    let node_types_path = fs::canonicalize(&node_types_path)
+        .map_err(|error| format!("failed to read {}: {error}", node_types_path.display()))?;
+    let json = fs::read_to_string(&node_types_path)
+        .map_err(|error| format!("failed to read {}: {error}", node_types_path.display()))?;
+    NodeTypes::try_parse(&json)
+        .map_err(|error| format!("{} is not valid: {error}", node_types_path.display()))?;
+
+    // Each distinct configuration is leaked once, so that it can be used like
+    // the grammars of the registry.
+    Ok(Some(Box::leak(Box::new(grammar.with_node_types(
+        Box::leak(json.into_boxed_str()),
+        node_types_path,
+    )))))
+}
@@ -0,0 +1,419 @@
+//! Parsing and code generation for `field_path!`.
+//!
+//! Every step is checked against the node kinds that can occur before it:
+//! those are unknown at the start of the path, unless it begins with a kind
+//! filter, and after that they are the types `node-types.json` lists for the
+//! field or the children the previous step went through.
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{bracketed, Expr, Token};
+use tree_sitter::Language;
+use tree_sitter_proc_core::node_types::{NodeType, NodeTypeRef, NodeTypes};
+use tree_sitter_proc_core::{diagnostics, symbols, LangName};
+
+use crate::grammar::{self, Grammar};
+
+/// Input of `field_path!`: the grammar, the node the path starts at and the
+/// steps.
+pub(crate) struct FieldPath {
+    grammar: &'static Grammar,
+    node: Expr,
+    start: Option<Filter>,
+    steps: Vec<Step>,
+}
+
+struct Step {
+    kind: StepKind,
+    filter: Option<Filter>,
+}
+
+enum StepKind {
+    /// `field`: the first child in the field.
+    Field(Ident),
+    /// `*`: the first named child that isn't an extra, such as a comment.
+    Any(Span),
+}
+
+/// `[kind | kind]`: the node must be of one of the kinds.
+struct Filter {
+    kinds: Punctuated<Ident, Token![|]>,
+}
+
+impl Parse for FieldPath {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let grammar = grammar::parse_leading(input)?;
+        let node = input.parse()?;
+        input.parse::<Token![=>]>()?;
+
+        let start = if input.peek(syn::token::Bracket) {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+
+        let mut steps = Vec::new();
+        loop {
+            steps.push(input.parse()?);
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![/]>()?;
+        }
+
+        Ok(FieldPath {
+            grammar,
+            node,
+            start,
+            steps,
+        })
+    }
+}
+
+impl Parse for Step {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let kind = if input.peek(Token![*]) {
+            StepKind::Any(input.parse::<Token![*]>()?.span)
+        } else if input.peek(Ident::peek_any) {
+            StepKind::Field(input.call(Ident::parse_any)?)
+        } else {
+            return Err(input.error("expected a field name or `*`"));
+        };
+
+        let filter = if input.peek(syn::token::Bracket) {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+
+        Ok(Step { kind, filter })
+    }
+}
+
+impl Parse for Filter {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        bracketed!(content in input);
+        let kinds = Punctuated::parse_separated_nonempty_with(&content, Ident::parse_any)?;
+        if !content.is_empty() {
+            return Err(content.error("expected `|` between the kinds of a filter"));
+        }
+        Ok(Filter { kinds })
+    }
+}
+
+/// Validates the path against the grammar and generates the chain of lookups.
+pub(crate) fn expand(input: FieldPath) -> syn::Result<TokenStream> {
+    let language = input.grammar.language();
+    let node_types = input.grammar.node_types();
+    let generator = Generator {
+        lang_name: input.grammar.lang_name(),
+        language: &language,
+        node_types: &node_types,
+    };
+
+    let label = syn::Lifetime::new("'field_path", Span::mixed_site());
+    let mismatch = quote!(break #label ::core::option::Option::None);
+    let first = format_ident!("node_0", span = Span::mixed_site());
+    let mut node = first.clone();
+    let scrutinee = &input.node;
+
+    // The node kinds that can occur at the current step, or `None` if any can
+    let mut kinds = None;
+    let mut lookups = Vec::new();
+
+    if let Some(filter) = &input.start {
+        let (ids, filtered) = generator.filter(filter, kinds)?;
+        kinds = filtered;
+        lookups.push(quote! {
+            if !::core::matches!(#node.kind_id(), #(#ids)|*) {
+                #mismatch;
+            }
+        });
+    }
+
+    for (index, step) in input.steps.iter().enumerate() {
+        let (lookup, children) = match &step.kind {
+            StepKind::Field(field) => {
+                let (field_id, children) = generator.field(field, kinds.as_deref())?;
+                let lookup = match &step.filter {
+                    None => quote!(#node.child_by_field_id(#field_id)),
+                    Some(_) => quote!(#node.children_by_field_id(#field_id, &mut cursor)),
+                };
+                (lookup, children)
+            }
+            StepKind::Any(span) => {
+                let children = generator.any(*span, kinds.as_deref())?;
+                // Extras such as comments can come before any named child
+                let lookup = match &step.filter {
+                    None => quote! {
+                        {
+                            let mut cursor = #node.walk();
+                            let found = #node
+                                .named_children(&mut cursor)
+                                .find(|child| !child.is_extra());
+                            found
+                        }
+                    },
+                    Some(_) => quote! {
+                        #node.named_children(&mut cursor).filter(|child| !child.is_extra())
+                    },
+                };
+                (lookup, children)
+            }
+        };
+
+        // Filtered steps search the candidates instead of taking the first
+        let lookup = match &step.filter {
+            None => {
+                kinds = children;
+                lookup
+            }
+            Some(filter) => {
+                let (ids, filtered) = generator.filter(filter, children)?;
+                kinds = filtered;
+                quote! {
+                    {
+                        let mut cursor = #node.walk();
+                        let found = #lookup
+                            .find(|child| ::core::matches!(child.kind_id(), #(#ids)|*));
+                        found
+                    }
+                }
+            }
+        };
+
+        let child = format_ident!("node_{}", index + 1, span = Span::mixed_site());
+        lookups.push(quote! {
+            // `let ... else` can't take a block expression
+            let #child = match #lookup {
+                ::core::option::Option::Some(#child) => #child,
+                ::core::option::Option::None => #mismatch,
+            };
+        });
+        node = child;
+    }
+
+    let dependencies = input.grammar.dependencies();
+    Ok(quote! {
+        {
+            #dependencies
+            let #first: ::tree_sitter::Node<'_> = #scrutinee;
+            #label: {
+                #(#lookups)*
+                ::core::option::Option::Some(#node)
+            }
+        }
+    })
+}
+
+struct Generator<'a> {
+    lang_name: LangName<'static>,
+    language: &'a Language,
+    node_types: &'a NodeTypes,
+}
+
+impl Generator<'_> {
+    /// Returns the ID of `field` and the kinds of the nodes in it, checking
+    /// that one of `kinds` can have it when they are known.
+    fn field(
+        &self,
+        field: &Ident,
+        kinds: Option<&[NodeTypeRef]>,
+    ) -> syn::Result<(u16, Option<Vec<NodeTypeRef>>)> {
+        let field_name = field.unraw().to_string();
+        let Some(field_id) = self.language.field_id_for_name(&field_name) else {
+            return Err(syn::Error::new(
+                field.span(),
+                diagnostics::unknown_field(self.language, self.lang_name, &field_name),
+            ));
+        };
+
+        let holders = self.node_types_of(kinds);
+        let fields: Vec<_> = holders
+            .iter()
+            .filter_map(|node_type| node_type.fields.get(&field_name))
+            .collect();
+
+        if fields.is_empty() {
+            // A field no node type lists can't be checked either
+            let Some(kinds) = kinds else {
+                return Ok((field_id, None));
+            };
+            let message = match holders.as_slice() {
+                [only] => {
+                    let names: Vec<_> = only.fields.keys().map(String::as_str).collect();
+                    diagnostics::field_not_in_kind(self.lang_name, &only.kind, &field_name, &names)
+                }
+                _ => {
+                    let mut names: Vec<_> = holders
+                        .iter()
+                        .flat_map(|node_type| node_type.fields.keys())
+                        .map(String::as_str)
+                        .collect();
+                    names.sort_unstable();
+                    names.dedup();
+                    diagnostics::field_not_in_kinds(
+                        self.lang_name,
+                        &named_kinds(kinds),
+                        &field_name,
+                        &names,
+                    )
+                }
+            };
+            return Err(syn::Error::new(field.span(), message));
+        }
+
+        let types = fields.iter().flat_map(|field| &field.types);
+        Ok((field_id, Some(self.concrete(types))))
+    }
+
+    /// Returns the kinds of the named children of `kinds`, checking that one of
+    /// them can have any when they are known.
+    fn any(
+        &self,
+        span: Span,
+        kinds: Option<&[NodeTypeRef]>,
+    ) -> syn::Result<Option<Vec<NodeTypeRef>>> {
+        let Some(kinds) = kinds else {
+            return Ok(None);
+        };
+
+        let types = self
+            .node_types_of(Some(kinds))
+            .into_iter()
+            .flat_map(|node_type| {
+                let fields = node_type.fields.values().flat_map(|field| &field.types);
+                fields.chain(
+                    node_type
+                        .children
+                        .iter()
+                        .flat_map(|children| &children.types),
+                )
+            });
+        let children: Vec<_> = self
+            .concrete(types)
+            .into_iter()
+            .filter(|child| child.named)
+            .collect();
+
+        if children.is_empty() {
+            return Err(syn::Error::new(
+                span,
+                "none of the nodes that can occur here have named children",
+            ));
+        }
+        Ok(Some(children))
+    }
+
+    /// Returns the IDs `filter` matches and the kinds in it that can occur
+    /// among `kinds`, checking that each of its kinds can.
+    ///
+    /// The kinds after a filter that lets `ERROR` through are unknown, since
+    /// anything can occur in one.
+    fn filter(
+        &self,
+        filter: &Filter,
+        kinds: Option<Vec<NodeTypeRef>>,
+    ) -> syn::Result<(Vec<u16>, Option<Vec<NodeTypeRef>>)> {
+        let mut ids = Vec::new();
+        let mut filtered = Vec::new();
+        let mut error = false;
+        for kind in &filter.kinds {
+            let name = kind.unraw().to_string();
+            // Unparseable input is reported as `ERROR`, which can occur
+            // anywhere and isn't in the symbol table.
+            if name == "ERROR" {
+                ids.push(u16::MAX);
+                error = true;
+                continue;
+            }
+
+            let matched = if !symbols::ids_for_node_kind(self.language, &name, true).is_empty() {
+                vec![NodeTypeRef {
+                    kind: name.clone(),
+                    named: true,
+                }]
+            } else if let Some(subtypes) = self.node_types.concrete_subtypes(&name) {
+                subtypes
+            } else {
+                return Err(syn::Error::new(
+                    kind.span(),
+                    diagnostics::unknown_kind(self.language, self.lang_name, &name),
+                ));
+            };
+
+            let reachable: Vec<_> = match &kinds {
+                Some(kinds) => matched
+                    .into_iter()
+                    .filter(|matched| kinds.contains(matched))
+                    .collect(),
+                None => matched,
+            };
+            if let (true, Some(kinds)) = (reachable.is_empty(), &kinds) {
+                return Err(syn::Error::new(
+                    kind.span(),
+                    diagnostics::kind_not_reachable(self.lang_name, &name, &named_kinds(kinds)),
+                ));
+            }
+
+            for kind in reachable {
+                ids.extend(symbols::ids_for_node_kind(
+                    self.language,
+                    &kind.kind,
+                    kind.named,
+                ));
+                if !filtered.contains(&kind) {
+                    filtered.push(kind);
+                }
+            }
+        }
+
+        Ok((ids, (!error).then_some(filtered)))
+    }
+
+    /// Returns the entries of `kinds` in `node-types.json`, or all of them if
+    /// the kinds are unknown.
+    fn node_types_of(&self, kinds: Option<&[NodeTypeRef]>) -> Vec<&NodeType> {
+        match kinds {
+            Some(kinds) => kinds
+                .iter()
+                .filter_map(|kind| self.node_types.node_type(&kind.kind, kind.named))
+                .collect(),
+            None => self.node_types.node_types().iter().collect(),
+        }
+    }
+
+    /// Returns `types` with every supertype replaced by its concrete subtypes,
+    /// without duplicates.
+    fn concrete<'t>(&self, types: impl IntoIterator<Item = &'t NodeTypeRef>) -> Vec<NodeTypeRef> {
+        let mut concrete = Vec::new();
+        for node_type in types {
+            let subtypes = match self.node_types.concrete_subtypes(&node_type.kind) {
+                Some(subtypes) if node_type.named => subtypes,
+                _ => vec![node_type.clone()],
+            };
+            for subtype in subtypes {
+                if !concrete.contains(&subtype) {
+                    concrete.push(subtype);
+                }
+            }
+        }
+        concrete
+    }
+}
+
+/// Returns the names of the named kinds among `kinds`, sorted.
+fn named_kinds(kinds: &[NodeTypeRef]) -> Vec<&str> {
+    let mut names: Vec<_> = kinds
+        .iter()
+        .filter(|kind| kind.named)
+        .map(|kind| kind.kind.as_str())
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
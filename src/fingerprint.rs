@@ -0,0 +1,55 @@
+//! Code generation for `grammar_fingerprint!`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use tree_sitter_c_proc_runtime::fingerprint;
+use tree_sitter_proc_core::symbols;
+
+use crate::grammar::Grammar;
+
+/// Generates a `Fingerprint` of the grammar's symbol and field tables.
+pub(crate) fn expand(grammar: &Grammar) -> TokenStream {
+    let language = grammar.language();
+    let hash = fingerprint::hash(&language);
+
+    // `ERROR` isn't part of the symbol table, so it can't move
+    let kinds = symbols::node_kinds(&language)
+        .into_iter()
+        .filter(|node_kind| node_kind.ids != [u16::MAX])
+        .map(|node_kind| {
+            let name = node_kind.name;
+            let named = node_kind.named;
+            let ids = node_kind.ids;
+            quote! {
+                ::tree_sitter_c_proc_runtime::fingerprint::Kind {
+                    name: #name,
+                    named: #named,
+                    ids: &[#(#ids),*],
+                }
+            }
+        });
+
+    let fields = symbols::field_ids(&language).filter_map(|id| {
+        let name = language.field_name_for_id(id)?;
+        Some(quote! {
+            ::tree_sitter_c_proc_runtime::fingerprint::Field {
+                name: #name,
+                id: #id,
+            }
+        })
+    });
+
+    let dependencies = grammar.dependencies();
+
+    quote! {
+        {
+            #dependencies
+
+            ::tree_sitter_c_proc_runtime::Fingerprint {
+                hash: #hash,
+                kinds: &[#(#kinds),*],
+                fields: &[#(#fields),*],
+            }
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! The registry of grammars the macros can look names up in.
+//!
+//! Every grammar is behind a `lang-*` cargo feature so that only the grammars a
+//! crate uses are compiled. Macros select a grammar with an optional first
+//! argument, such as `kind!(cpp, "class_specifier")`, and fall back to the
+//! grammar the calling crate's manifest selects, or to C.
+//!
+//! A grammar's crate can be replaced by a grammar on disk, see `grammar_dir`.
+
+use std::path::PathBuf;
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::Token;
+use tree_sitter::Language;
+use tree_sitter_proc_core::node_types::NodeTypes;
+use tree_sitter_proc_core::LangName;
+
+use crate::config;
+use crate::grammar_dir;
+
+/// A tree-sitter grammar the macros can look names up in.
+#[derive(Clone, Copy)]
+pub(crate) struct Grammar {
+    /// The identifier that selects the grammar in macro arguments.
+    pub name: &'static str,
+    /// The name of the grammar's crate, used in error messages.
+    pub crate_name: &'static str,
+    /// The prefix of generated type names, such as the `C` in `CKind`.
+    pub type_prefix: &'static str,
+    language: LanguageSource,
+    node_types: &'static str,
+    /// The files on disk the grammar was read from, if any.
+    files: &'static [PathBuf],
+}
+
+#[derive(Clone, Copy)]
+enum LanguageSource {
+    /// The `language()` function of the grammar's crate.
+    Bundled(fn() -> Language),
+    /// A language loaded from a shared library.
+    Loaded(Language),
+}
+
+impl Grammar {
+    /// Returns the grammar's `Language`.
+    pub(crate) fn language(&self) -> Language {
+        match self.language {
+            LanguageSource::Bundled(language) => language(),
+            LanguageSource::Loaded(language) => language,
+        }
+    }
+
+    /// Parses the grammar's `node-types.json`.
+    pub(crate) fn node_types(&self) -> NodeTypes {
+        NodeTypes::parse(self.node_types)
+    }
+
+    /// Returns how the macros expanded by `tree_sitter_proc_core` refer to the
+    /// grammar, naming it in suggested invocations unless it is the default.
+    pub(crate) fn lang_name(&self) -> LangName<'static> {
+        let lang_name = LangName::new(self.crate_name).with_node_types(self.node_types);
+        if is_default(self) {
+            lang_name
+        } else {
+            lang_name.with_argument(self.name)
+        }
+    }
+
+    /// Returns the grammar with its `node-types.json` replaced by `json`, read
+    /// from `path`.
+    pub(crate) fn with_node_types(&self, json: &'static str, path: PathBuf) -> Grammar {
+        Grammar {
+            node_types: json,
+            files: Box::leak(Box::new([path])),
+            ..*self
+        }
+    }
+
+    /// Returns the grammar with its `Language` and `node-types.json` replaced
+    /// by ones read from `files`.
+    pub(crate) fn with_language(
+        &self,
+        language: Language,
+        node_types: &'static str,
+        files: Vec<PathBuf>,
+    ) -> Grammar {
+        Grammar {
+            language: LanguageSource::Loaded(language),
+            node_types,
+            files: Box::leak(files.into_boxed_slice()),
+            ..*self
+        }
+    }
+
+    /// Generates items that make cargo rebuild the calling crate when the
+    /// grammar's files on disk change, when the variable that points at a
+    /// grammar directory is set, changed or removed, or when the calling
+    /// crate's manifest changes, which can select another grammar.
+    ///
+    /// Proc macros can't declare dependencies on stable Rust, but `rustc`
+    /// records the files read by `include_bytes!` and the variables read by
+    /// `option_env!`, and cargo rebuilds when those change. Cargo itself
+    /// ignores changes to `[package.metadata]`.
+    pub(crate) fn dependencies(&self) -> TokenStream {
+        let variable = grammar_dir::variable(self);
+        let files = self
+            .files
+            .iter()
+            .cloned()
+            .chain(config::manifest_path())
+            .map(|file| file.to_string_lossy().into_owned());
+        quote! {
+            const _: ::core::option::Option<&str> = ::core::option_env!(#variable);
+            #(
+                const _: &[u8] = ::core::include_bytes!(#files);
+            )*
+        }
+    }
+
+    /// Returns the path of the grammar's crate, for generated code that needs
+    /// the `Language` at runtime.
+    pub(crate) fn crate_path(&self) -> TokenStream {
+        let ident = Ident::new(&self.crate_name.replace('-', "_"), Span::call_site());
+        quote!(::#ident)
+    }
+}
+
+/// Every grammar enabled by a cargo feature.
+const GRAMMARS: &[Grammar] = &[
+    #[cfg(feature = "lang-c")]
+    Grammar {
+        name: "c",
+        crate_name: "tree-sitter-c",
+        type_prefix: "C",
+        language: LanguageSource::Bundled(tree_sitter_c::language),
+        node_types: tree_sitter_c::NODE_TYPES,
+        files: &[],
+    },
+    #[cfg(feature = "lang-cpp")]
+    Grammar {
+        name: "cpp",
+        crate_name: "tree-sitter-cpp",
+        type_prefix: "Cpp",
+        language: LanguageSource::Bundled(tree_sitter_cpp::language),
+        node_types: tree_sitter_cpp::NODE_TYPES,
+        files: &[],
+    },
+    #[cfg(feature = "lang-rust")]
+    Grammar {
+        name: "rust",
+        crate_name: "tree-sitter-rust",
+        type_prefix: "Rust",
+        language: LanguageSource::Bundled(tree_sitter_rust::language),
+        node_types: tree_sitter_rust::NODE_TYPES,
+        files: &[],
+    },
+];
+
+/// Every grammar the crate supports and the feature that enables it.
+const FEATURES: &[(&str, &str)] = &[("c", "lang-c"), ("cpp", "lang-cpp"), ("rust", "lang-rust")];
+
+/// The grammar used when neither the macro nor the manifest names one.
+pub(crate) const DEFAULT: &str = "c";
+
+/// Returns the grammar selected by `ident`.
+pub(crate) fn lookup(ident: &Ident) -> syn::Result<&'static Grammar> {
+    find(&ident.to_string())
+        .and_then(grammar_dir::apply)
+        .map_err(|message| syn::Error::new(ident.span(), message))
+}
+
+/// Returns the grammar of the registry called `name`, or an error message
+/// saying why it isn't available.
+pub(crate) fn find(name: &str) -> Result<&'static Grammar, String> {
+    if let Some(grammar) = GRAMMARS.iter().find(|grammar| grammar.name == name) {
+        return Ok(grammar);
+    }
+
+    Err(match FEATURES.iter().find(|(known, _)| *known == name) {
+        Some((_, feature)) => format!(
+            "the `{name}` grammar is not enabled; enable the `{feature}` feature of \
+             tree-sitter-c-proc to use it"
+        ),
+        None => {
+            let known: Vec<String> = FEATURES
+                .iter()
+                .map(|(known, _)| format!("`{known}`"))
+                .collect();
+            format!(
+                "`{name}` is not a supported grammar; the supported grammars are {}",
+                known.join(", ")
+            )
+        }
+    })
+}
+
+/// Returns the grammar used when a macro doesn't name one.
+pub(crate) fn default_grammar(span: Span) -> syn::Result<&'static Grammar> {
+    config::default_grammar()
+        .and_then(grammar_dir::apply)
+        .map_err(|message| syn::Error::new(span, message))
+}
+
+/// Returns `true` if macros use `grammar` when they don't name one.
+pub(crate) fn is_default(grammar: &Grammar) -> bool {
+    config::default_grammar().is_ok_and(|default| default.name == grammar.name)
+}
+
+/// Returns the grammar named by `DEFAULT`, for crates that don't configure
+/// one.
+pub(crate) fn builtin_default() -> Result<&'static Grammar, String> {
+    find(DEFAULT).map_err(|_| {
+        format!(
+            "the default `{DEFAULT}` grammar is not enabled; name a grammar as the first \
+             argument, such as `kind!(cpp, \"...\")`, select one in \
+             [package.metadata.tree-sitter-proc], or enable the `lang-{DEFAULT}` feature"
+        )
+    })
+}
+
+/// Parses the optional grammar argument that starts a macro's input, such as
+/// `cpp,` in `kind!(cpp, "class_specifier")`, and returns the selected grammar.
+pub(crate) fn parse_leading(input: ParseStream) -> syn::Result<&'static Grammar> {
+    if input.peek(syn::Ident) && input.peek2(Token![,]) {
+        let ident: Ident = input.parse()?;
+        input.parse::<Token![,]>()?;
+        lookup(&ident)
+    } else {
+        default_grammar(input.span())
+    }
+}
+
+/// Input of the macros whose only argument is the optional grammar, such as
+/// `node_kind_enum!()` and `node_kind_enum!(cpp)`.
+pub(crate) struct GrammarOnly(pub &'static Grammar);
+
+impl Parse for GrammarOnly {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let grammar = if input.is_empty() {
+            default_grammar(input.span())?
+        } else {
+            let ident: Ident = input.parse()?;
+            input.parse::<Option<Token![,]>>()?;
+            lookup(&ident)?
+        };
+        Ok(GrammarOnly(grammar))
+    }
+}
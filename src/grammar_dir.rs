@@ -0,0 +1,223 @@
+//! Grammars read from a directory on disk instead of a grammar's crate.
+//!
+//! Setting `TREE_SITTER_C_GRAMMAR_DIR`, or the variable of another grammar
+//! such as `TREE_SITTER_CPP_GRAMMAR_DIR`, to the directory of a grammar makes
+//! the macros validate names against that grammar, for example one that is
+//! being extended. The directory needs:
+//!
+//! * `src/grammar.json`, for the name of the grammar;
+//! * `src/node-types.json`, for supertypes and fields;
+//! * the parser compiled into a shared library, named `parser.so` (or
+//!   `parser.dylib`, `parser.dll`), `<name>.so` or `libtree-sitter-<name>.so`,
+//!   in the directory or in `src`.
+//!
+//! Symbol and field IDs are only assigned when `tree-sitter generate` writes
+//! `parser.c`, so they are read from the compiled parser rather than computed
+//! from `grammar.json`.
+
+use std::env;
+use std::env::consts::{DLL_EXTENSION, DLL_PREFIX};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use serde::Deserialize;
+use tree_sitter::{Language, LANGUAGE_VERSION, MIN_COMPATIBLE_LANGUAGE_VERSION};
+use tree_sitter_proc_core::node_types::NodeTypes;
+
+use crate::grammar::Grammar;
+
+/// The parts of `grammar.json` the macros read.
+#[derive(Deserialize)]
+struct GrammarJson {
+    name: String,
+}
+
+/// A grammar directory that was loaded before.
+struct Loaded {
+    base: &'static str,
+    files: Vec<PathBuf>,
+    modified: Vec<SystemTime>,
+    grammar: &'static Grammar,
+}
+
+/// Directories are cached by the modification time of their files, because a
+/// long-lived process such as rust-analyzer keeps expanding the macros while
+/// the grammar is regenerated.
+static LOADED: Mutex<Vec<Loaded>> = Mutex::new(Vec::new());
+
+/// Returns the environment variable that points at a directory replacing
+/// `grammar`, such as `TREE_SITTER_C_GRAMMAR_DIR`.
+pub(crate) fn variable(grammar: &Grammar) -> String {
+    format!("TREE_SITTER_{}_GRAMMAR_DIR", grammar.name.to_uppercase())
+}
+
+/// Returns `grammar`, or the grammar in the directory its environment variable
+/// points at if it is set.
+///
+/// Returns an error message if the directory is missing a file, or if its
+/// parser can't be loaded.
+pub(crate) fn apply(grammar: &'static Grammar) -> Result<&'static Grammar, String> {
+    let variable = variable(grammar);
+    let Some(directory) = env::var_os(&variable).filter(|directory| !directory.is_empty()) else {
+        return Ok(grammar);
+    };
+    let context = |message: String| format!("failed to load the grammar in {variable}: {message}");
+
+    let directory = canonicalize(Path::new(&directory)).map_err(context)?;
+    let source = directory.join("src");
+    let grammar_json = canonicalize(&source.join("grammar.json")).map_err(context)?;
+    let node_types_json = canonicalize(&source.join("node-types.json")).map_err(context)?;
+
+    let name = serde_json::from_str::<GrammarJson>(&read(&grammar_json).map_err(context)?)
+        .map_err(|error| context(format!("{} is not valid: {error}", grammar_json.display())))?
+        .name;
+    let library = find_library(&[&directory, &source], &name).map_err(context)?;
+
+    let files = vec![grammar_json, node_types_json, library];
+    let modified = files
+        .iter()
+        .map(|file| fs::metadata(file).and_then(|metadata| metadata.modified()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|error| context(error.to_string()))?;
+
+    let mut loaded = LOADED.lock().unwrap_or_else(|error| error.into_inner());
+    if let Some(previous) = loaded.iter().find(|previous| {
+        previous.base == grammar.name && previous.files == files && previous.modified == modified
+    }) {
+        return Ok(previous.grammar);
+    }
+
+    // A library that is already loaded is only loaded again from another path
+    let reloaded = loaded.iter().any(|previous| previous.files[2] == files[2]);
+    let language = load_language(&files[2], &name, reloaded).map_err(context)?;
+
+    let json = read(&files[1]).map_err(context)?;
+    NodeTypes::try_parse(&json)
+        .map_err(|error| context(format!("{} is not valid: {error}", files[1].display())))?;
+
+    // Each version of the grammar is leaked once, so that it can be used like
+    // the grammars of the registry.
+    let replaced: &'static Grammar = Box::leak(Box::new(grammar.with_language(
+        language,
+        Box::leak(json.into_boxed_str()),
+        files.clone(),
+    )));
+    loaded.push(Loaded {
+        base: grammar.name,
+        files,
+        modified,
+        grammar: replaced,
+    });
+    Ok(replaced)
+}
+
+/// Returns the path of the compiled parser in one of `directories`.
+fn find_library(directories: &[&Path], name: &str) -> Result<PathBuf, String> {
+    let candidates = [
+        format!("parser.{DLL_EXTENSION}"),
+        format!("{name}.{DLL_EXTENSION}"),
+        format!("{DLL_PREFIX}tree-sitter-{name}.{DLL_EXTENSION}"),
+    ];
+    directories
+        .iter()
+        .flat_map(|directory| candidates.iter().map(|file| directory.join(file)))
+        .find(|path| path.is_file())
+        .ok_or_else(|| {
+            format!(
+                "found no compiled parser in {}; build one with \
+                 `cc -shared -fPIC -Isrc src/parser.c src/scanner.c -o parser.{DLL_EXTENSION}`, \
+                 leaving out `src/scanner.c` if the grammar has no scanner",
+                directories[0].display()
+            )
+        })
+}
+
+/// Loads the `tree_sitter_<name>` function from the shared library at
+/// `library` and returns its `Language`.
+///
+/// `dlopen` returns the library that is already loaded for a path, so a
+/// library that changed since it was loaded (`reload`) is copied to a new path
+/// first.
+fn load_language(library: &Path, name: &str, reload: bool) -> Result<Language, String> {
+    let copy = if reload {
+        Some(copy_library(library)?)
+    } else {
+        None
+    };
+    let path = copy.as_deref().unwrap_or(library);
+
+    let symbol = format!("tree_sitter_{}", name.replace('-', "_"));
+    // SAFETY: loading a grammar runs no initialization code, and the symbol
+    // has the signature tree-sitter generates for every grammar.
+    let language = unsafe {
+        let loaded = libloading::Library::new(path);
+        // A loaded library stays mapped after its file is removed, except on
+        // Windows, where `copy_library` removes it later
+        if let Some(copy) = &copy {
+            let _ = fs::remove_file(copy);
+        }
+        let loaded =
+            loaded.map_err(|error| format!("failed to load {}: {error}", library.display()))?;
+        let language_fn = loaded
+            .get::<unsafe extern "C" fn() -> Language>(symbol.as_bytes())
+            .map_err(|error| {
+                format!("{} has no `{symbol}` function: {error}", library.display())
+            })?;
+        let language = language_fn();
+        // The `Language` points into the library, so it stays loaded for the
+        // rest of the process.
+        std::mem::forget(loaded);
+        language
+    };
+
+    let version = language.version();
+    if !(MIN_COMPATIBLE_LANGUAGE_VERSION..=LANGUAGE_VERSION).contains(&version) {
+        return Err(format!(
+            "{} was generated for version {version} of the tree-sitter language ABI, but \
+             tree-sitter-c-proc supports versions {MIN_COMPATIBLE_LANGUAGE_VERSION} to \
+             {LANGUAGE_VERSION}; regenerate it with a matching tree-sitter CLI",
+            library.display()
+        ));
+    }
+    Ok(language)
+}
+
+/// Copies `library` to a new path in a temporary directory of its own.
+///
+/// Windows doesn't remove files that are loaded, so there the copies of
+/// earlier loads are removed here, once no process has them loaded anymore.
+fn copy_library(library: &Path) -> Result<PathBuf, String> {
+    let directory = env::temp_dir().join("tree-sitter-c-proc");
+    fs::create_dir_all(&directory)
+        .map_err(|error| format!("failed to create {}: {error}", directory.display()))?;
+    if cfg!(windows) {
+        for entry in fs::read_dir(&directory).into_iter().flatten().flatten() {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+
+    let unique = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos());
+    let copy = directory.join(format!("{}-{unique}.{DLL_EXTENSION}", std::process::id()));
+    fs::copy(library, &copy)
+        .map_err(|error| format!("failed to copy {}: {error}", library.display()))?;
+    Ok(copy)
+}
+
+/// Returns the absolute path of `path`, for the `include_bytes!` that tracks
+/// it.
+fn canonicalize(path: &Path) -> Result<PathBuf, String> {
+    fs::canonicalize(path).map_err(|error| format!("failed to read {}: {error}", path.display()))
+}
+
+/// Returns the contents of the text file at `path`, `grammar.json` or
+/// `node-types.json` of a directory.
+///
+/// Returns an error message naming `path` if the file is missing or isn't
+/// UTF-8.
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|error| format!("failed to read {}: {error}", path.display()))
+}
@@ -0,0 +1,103 @@
+//! Code generation for `node_kind_enum!`.
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use tree_sitter_proc_core::naming;
+use tree_sitter_proc_core::symbols;
+
+use crate::grammar::Grammar;
+
+/// Generates the `CKind` enum, or its equivalent for another grammar, with one
+/// variant per node kind of the grammar.
+pub(crate) fn expand(grammar: &Grammar) -> TokenStream {
+    let node_kinds = symbols::node_kinds(&grammar.language());
+    let kind = format_ident!("{}Kind", grammar.type_prefix);
+    let doc = format!(
+        "Every node kind of the {} grammar, named and anonymous.",
+        grammar.crate_name
+    );
+    let variants: Vec<Ident> = naming::node_kind_idents(&node_kinds)
+        .iter()
+        .map(|ident| Ident::new(ident, Span::call_site()))
+        .collect();
+
+    let docs = node_kinds.iter().map(|node_kind| {
+        if node_kind.named {
+            format!("The `{}` node kind.", node_kind.name)
+        } else {
+            format!("The `{:?}` token.", node_kind.name)
+        }
+    });
+    // Every variant's discriminant is the ID `Node::kind_id` reports.
+    let discriminants = node_kinds.iter().map(|node_kind| node_kind.id);
+    let names = node_kinds.iter().map(|node_kind| node_kind.name);
+    let nameds = node_kinds.iter().map(|node_kind| node_kind.named);
+    let id_patterns = node_kinds.iter().map(|node_kind| {
+        let ids = &node_kind.ids;
+        quote!(#(#ids)|*)
+    });
+
+    quote! {
+        #[doc = #doc]
+        ///
+        /// Each discriminant is the node kind ID reported by `Node::kind_id`, so
+        /// matching on the result of `try_from(node.kind_id())` is checked for
+        /// exhaustiveness by the compiler.
+        #[derive(
+            ::core::clone::Clone,
+            ::core::marker::Copy,
+            ::core::fmt::Debug,
+            ::core::cmp::PartialEq,
+            ::core::cmp::Eq,
+            ::core::hash::Hash
+        )]
+        #[repr(u16)]
+        pub enum #kind {
+            #(
+                #[doc = #docs]
+                #variants = #discriminants,
+            )*
+        }
+
+        impl #kind {
+            /// Returns the node kind ID of this kind.
+            pub const fn id(self) -> u16 {
+                self as u16
+            }
+
+            /// Returns the name of this kind, as reported by `Node::kind`.
+            pub const fn as_str(self) -> &'static str {
+                match self {
+                    #(#kind::#variants => #names,)*
+                }
+            }
+
+            /// Returns `true` if this is a named node kind rather than an
+            /// anonymous token.
+            pub const fn is_named(self) -> bool {
+                match self {
+                    #(#kind::#variants => #nameds,)*
+                }
+            }
+        }
+
+        impl ::core::convert::TryFrom<u16> for #kind {
+            type Error = u16;
+
+            /// Converts a node kind ID into a node kind, accepting every symbol ID
+            /// of aliased kinds. Unknown IDs are returned as the error.
+            fn try_from(id: u16) -> ::core::result::Result<Self, u16> {
+                match id {
+                    #(#id_patterns => ::core::result::Result::Ok(#kind::#variants),)*
+                    _ => ::core::result::Result::Err(id),
+                }
+            }
+        }
+
+        impl ::core::fmt::Display for #kind {
+            fn fmt(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                formatter.write_str(self.as_str())
+            }
+        }
+    }
+}
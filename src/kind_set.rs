@@ -0,0 +1,84 @@
+//! Parsing and code generation for `kind_set!`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{LitStr, Token};
+use tree_sitter_proc_core::{diagnostics, symbols};
+
+use crate::grammar::{self, Grammar};
+
+/// Input of `kind_set!`: node kind or supertype names, optionally preceded by
+/// the grammar.
+pub(crate) struct KindSetInput {
+    grammar: &'static Grammar,
+    names: Punctuated<LitStr, Token![,]>,
+}
+
+impl Parse for KindSetInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let grammar = grammar::parse_leading(input)?;
+        let names = Punctuated::parse_terminated(input)?;
+        Ok(KindSetInput { grammar, names })
+    }
+}
+
+/// Generates a `KindSet` with every ID of the named kinds, sized to the
+/// grammar's symbol count.
+///
+/// Every unknown name is reported with its own span.
+pub(crate) fn expand(input: KindSetInput) -> syn::Result<TokenStream> {
+    let KindSetInput { grammar, names } = input;
+    let language = grammar.language();
+    let lang_name = grammar.lang_name();
+
+    let mut ids = Vec::new();
+    let mut errors: Option<syn::Error> = None;
+    for name in &names {
+        let requested = name.value();
+        // `ERROR` isn't part of the symbol table, but nodes report its ID
+        if requested == "ERROR" {
+            ids.push(u16::MAX);
+            continue;
+        }
+        let found_ids = symbols::ids_for_node_kind(&language, &requested, true);
+        if !found_ids.is_empty() {
+            ids.extend(found_ids);
+            continue;
+        }
+
+        // Supertypes never appear in a tree, so they stand for their subtypes
+        if let Some(subtypes) = grammar.node_types().concrete_subtypes(&requested) {
+            for subtype in subtypes {
+                ids.extend(symbols::ids_for_node_kind(
+                    &language,
+                    &subtype.kind,
+                    subtype.named,
+                ));
+            }
+            continue;
+        }
+
+        let message = diagnostics::unknown_kind(&language, lang_name, &requested);
+        let error = syn::Error::new(name.span(), message);
+        match &mut errors {
+            Some(errors) => errors.combine(error),
+            None => errors = Some(error),
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    let words = language.node_kind_count().div_ceil(64);
+    let dependencies = grammar.dependencies();
+
+    Ok(quote! {
+        {
+            #dependencies
+
+            ::tree_sitter_c_proc_runtime::KindSet::<#words>::from_ids(&[#(#ids),*])
+        }
+    })
+}
@@ -1,7 +1,26 @@
 use proc_macro::TokenStream;
 
-use quote::{quote, quote_spanned};
-use syn::{parse_macro_input, LitStr};
+use proc_macro2::{Ident, Span};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, DeriveInput, Token};
+use tree_sitter_proc_core::LangName;
+
+use grammar::{Grammar, GrammarOnly};
+
+mod config;
+mod field_path;
+mod fingerprint;
+mod grammar;
+mod grammar_dir;
+mod kind_enum;
+mod kind_set;
+mod match_node;
+mod query;
+mod query_derive;
+mod symbol_table;
+mod typed_nodes;
+mod visitor;
 
 /// Returns the node kind ID for a given node kind name.
 ///
@@ -9,45 +28,146 @@ use syn::{parse_macro_input, LitStr};
 ///
 /// # Arguments
 ///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`,
+///   followed by a comma. Defaults to the grammar selected in the calling
+///   crate's `[package.metadata.tree-sitter-proc]`, or `c`.
+/// * `typed` - An optional `typed` identifier, followed by a comma, that makes
+///   the IDs `tree_sitter_c_proc_runtime::KindId` values instead of bare integers.
 /// * `kind_name` - A string literal representing the node kind name.
 ///
 /// # Returns
 ///
-/// The node kind ID as a `u16`.
+/// The node kind ID as a `u16`, or as a `tree_sitter_c_proc_runtime::KindId`
+/// with `typed`.
+///
+/// Some names belong to several symbol IDs, for example when a rule is aliased
+/// in the grammar. Nodes of such a kind all report the same one of them from
+/// `Node::kind_id`, which is the ID this macro returns; `kinds!` matches every
+/// one of them.
 ///
 /// # Errors
 ///
 /// Generates a compile-time error if the provided node kind name is not valid
-/// in the tree-sitter-c grammar.
+/// in the grammar. The error suggests the closest node kinds, or
+/// points at `kw!` when the name only exists as an anonymous token.
 ///
 /// # Examples
 ///
 /// ```
 /// use tree_sitter_c_proc::kind;
 ///
-/// let function_def_id = kind!("function_definition");
+/// let if_statement_id = kind!("if_statement");
+/// let translation_unit_id = kind!("translation_unit");
+///
+/// match translation_unit_id {
+///     kind!("if_statement") => {}
+///     kind!("translation_unit") => {}
+///     _ => unreachable!(),
+/// }
 /// ```
 #[proc_macro]
 pub fn kind(token_stream: TokenStream) -> TokenStream {
-    let string_literal: LitStr = parse_macro_input!(token_stream);
-
-    // Get the string value
-    let requested_kind = string_literal.value();
-
-    let language = tree_sitter_c::language();
-    let found_id = language.id_for_node_kind(&requested_kind, true);
-
-    if found_id != 0 {
-        quote! {
-            #found_id
-        }
-    } else {
-        quote_spanned!(
-            string_literal.span() =>
-            compile_error!("This is not a valid node kind in the tree-sitter-c grammar")
-        )
-    }
-    .into()
+    let Forwarded {
+        grammar,
+        lang_name,
+        tokens,
+        ..
+    } = parse_macro_input!(token_stream);
+
+    tree_sitter_proc_core::expand_kind(&grammar.language(), tokens, lang_name).into()
+}
+
+/// Returns an or-pattern matching any of several node kinds.
+///
+/// This macro replaces chains such as `kind!("a") | kind!("b") | kind!("c")` in
+/// match arms and `matches!`.
+///
+/// # Arguments
+///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`,
+///   followed by a comma. Defaults to the grammar selected in the calling
+///   crate's `[package.metadata.tree-sitter-proc]`, or `c`.
+/// * `typed` - An optional `typed` identifier, followed by a comma, that makes
+///   the IDs `tree_sitter_c_proc_runtime::KindId` values instead of bare integers.
+/// * `kind_names` - A comma-separated list of string literals representing node kind names.
+///
+/// # Returns
+///
+/// An or-pattern of the node kind IDs, including every ID of names that belong
+/// to several symbol IDs. The expansion is only valid in pattern position;
+/// using it as an expression is a compile error.
+///
+/// # Errors
+///
+/// Generates a compile-time error for every name that is not a valid node kind
+/// in the grammar, each pointing at the offending literal.
+///
+/// # Examples
+///
+/// ```
+/// use tree_sitter_c_proc::{kind, kinds};
+///
+/// let kind_id = kind!("cast_expression");
+/// assert!(matches!(kind_id, kinds!("call_expression", "cast_expression")));
+/// ```
+#[proc_macro]
+pub fn kinds(token_stream: TokenStream) -> TokenStream {
+    let Forwarded {
+        grammar,
+        lang_name,
+        tokens,
+        ..
+    } = parse_macro_input!(token_stream);
+
+    tree_sitter_proc_core::expand_kinds(&grammar.language(), tokens, lang_name).into()
+}
+
+/// Returns an or-pattern matching every concrete subtype of a supertype.
+///
+/// Supertypes such as `_expression`, `_statement`, `_declarator` and
+/// `_type_specifier` never appear in a syntax tree themselves, so they can't be
+/// matched with `kind!`. This macro reads the grammar's `node-types.json` at
+/// compile time and expands the supertype into all of its subtypes, expanding
+/// nested supertypes recursively.
+///
+/// # Arguments
+///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`,
+///   followed by a comma. Defaults to the grammar selected in the calling
+///   crate's `[package.metadata.tree-sitter-proc]`, or `c`.
+/// * `typed` - An optional `typed` identifier, followed by a comma, that makes
+///   the IDs `tree_sitter_c_proc_runtime::KindId` values instead of bare integers.
+/// * `supertype_name` - A string literal representing the supertype name.
+///
+/// # Returns
+///
+/// An or-pattern of the node kind IDs of all concrete subtypes. The expansion
+/// is only valid in pattern position; using it as an expression is a compile
+/// error.
+///
+/// # Errors
+///
+/// Generates a compile-time error if the provided name is not a supertype in
+/// the grammar. The error suggests the closest supertypes.
+///
+/// # Examples
+///
+/// ```
+/// use tree_sitter_c_proc::{kind, supertype};
+///
+/// let kind_id = kind!("cast_expression");
+/// assert!(matches!(kind_id, supertype!("_expression")));
+/// ```
+#[proc_macro]
+pub fn supertype(token_stream: TokenStream) -> TokenStream {
+    let Forwarded {
+        grammar,
+        lang_name,
+        tokens,
+        ..
+    } = parse_macro_input!(token_stream);
+
+    tree_sitter_proc_core::expand_supertype(&grammar.language(), tokens, lang_name).into()
 }
 
 /// Returns the node kind ID for a given keyword.
@@ -56,16 +176,26 @@ pub fn kind(token_stream: TokenStream) -> TokenStream {
 ///
 /// # Arguments
 ///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`,
+///   followed by a comma. Defaults to the grammar selected in the calling
+///   crate's `[package.metadata.tree-sitter-proc]`, or `c`.
+/// * `typed` - An optional `typed` identifier, followed by a comma, that makes
+///   the IDs `tree_sitter_c_proc_runtime::TokenId` values instead of bare integers.
 /// * `keyword` - A string literal representing the keyword.
 ///
 /// # Returns
 ///
-/// The keyword ID as a `u16`.
+/// The keyword ID as a `u16`, or as a `tree_sitter_c_proc_runtime::TokenId`
+/// with `typed`.
+///
+/// Like `kind!`, a keyword that belongs to several symbol IDs returns the one
+/// `Node::kind_id` reports; `kws!` matches every one of them.
 ///
 /// # Errors
 ///
 /// Generates a compile-time error if the provided keyword is not valid
-/// in the tree-sitter-c grammar.
+/// in the grammar. The error suggests the closest keywords, or
+/// points at `kind!` when the name only exists as a named node kind.
 ///
 /// # Examples
 ///
@@ -76,25 +206,61 @@ pub fn kind(token_stream: TokenStream) -> TokenStream {
 /// ```
 #[proc_macro]
 pub fn kw(token_stream: TokenStream) -> TokenStream {
-    let string_literal: LitStr = parse_macro_input!(token_stream);
-
-    // Get the string value
-    let requested_keyword = string_literal.value();
-
-    let language = tree_sitter_c::language();
-    let found_id = language.id_for_node_kind(&requested_keyword, false);
-
-    if found_id != 0 {
-        quote! {
-            #found_id
-        }
-    } else {
-        quote_spanned!(
-            string_literal.span() =>
-            compile_error!("This is not a valid keyword in the tree-sitter-c grammar")
-        )
-    }
-    .into()
+    let Forwarded {
+        grammar,
+        lang_name,
+        tokens,
+        ..
+    } = parse_macro_input!(token_stream);
+
+    tree_sitter_proc_core::expand_kw(&grammar.language(), tokens, lang_name).into()
+}
+
+/// Returns an or-pattern matching any of several keywords or other anonymous
+/// tokens.
+///
+/// This is `kinds!` for anonymous tokens.
+///
+/// # Arguments
+///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`,
+///   followed by a comma. Defaults to the grammar selected in the calling
+///   crate's `[package.metadata.tree-sitter-proc]`, or `c`.
+/// * `typed` - An optional `typed` identifier, followed by a comma, that makes
+///   the IDs `tree_sitter_c_proc_runtime::TokenId` values instead of bare integers.
+/// * `keywords` - A comma-separated list of string literals representing
+///   keywords or other anonymous tokens.
+///
+/// # Returns
+///
+/// An or-pattern of the keyword IDs, including every ID of keywords that
+/// belong to several symbol IDs, such as `"("` in tree-sitter-c. The expansion
+/// is only valid in pattern position; using it as an expression is a compile
+/// error.
+///
+/// # Errors
+///
+/// Generates a compile-time error for every name that is not a valid keyword
+/// in the grammar, each pointing at the offending literal.
+///
+/// # Examples
+///
+/// ```
+/// use tree_sitter_c_proc::{kw, kws};
+///
+/// let keyword_id = kw!("while");
+/// assert!(matches!(keyword_id, kws!("for", "while", "do")));
+/// ```
+#[proc_macro]
+pub fn kws(token_stream: TokenStream) -> TokenStream {
+    let Forwarded {
+        grammar,
+        lang_name,
+        tokens,
+        ..
+    } = parse_macro_input!(token_stream);
+
+    tree_sitter_proc_core::expand_kws(&grammar.language(), tokens, lang_name).into()
 }
 
 /// Returns the field ID for a given field name.
@@ -103,44 +269,848 @@ pub fn kw(token_stream: TokenStream) -> TokenStream {
 ///
 /// # Arguments
 ///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`,
+///   followed by a comma. Defaults to the grammar selected in the calling
+///   crate's `[package.metadata.tree-sitter-proc]`, or `c`.
+/// * `typed` - An optional `typed` identifier, followed by a comma, that makes
+///   the ID a `tree_sitter_c_proc_runtime::FieldId` instead of a `NonZeroU16`.
+/// * `kind_name` - An optional string literal representing the node kind the
+///   field must belong to, followed by a comma.
 /// * `field_name` - A string literal representing the field name.
 ///
 /// # Returns
 ///
-/// The field ID as a `std::num::NonZeroU16`.
+/// The field ID as a `core::num::NonZeroU16`. The expansion is a constant
+/// expression, so it can initialize `const` and `static` items. It only refers
+/// to `::core`, so it works in `no_std` crates and when `std` is shadowed.
+///
+/// With `typed`, the field ID is a `tree_sitter_c_proc_runtime::FieldId`
+/// instead.
 ///
 /// # Errors
 ///
 /// Generates a compile-time error if the provided field name is not valid
-/// in the tree-sitter-c grammar.
+/// in the grammar. The error suggests the closest field names.
+///
+/// When a node kind is given, generates a compile-time error if the grammar's
+/// `node-types.json` doesn't list the field for that kind. The error lists the
+/// fields the kind does have.
 ///
 /// # Examples
 ///
 /// ```
+/// use std::num::NonZeroU16;
 /// use tree_sitter_c_proc::field;
 ///
 /// let declarator_field_id = field!("declarator");
+///
+/// const BODY_FIELD_ID: NonZeroU16 = field!("body");
+///
+/// // Also checks that function definitions can have a declarator
+/// let function_declarator_field_id = field!("function_definition", "declarator");
+/// assert_eq!(function_declarator_field_id, declarator_field_id);
 /// ```
 #[proc_macro]
 pub fn field(token_stream: TokenStream) -> TokenStream {
-    let string_literal: LitStr = parse_macro_input!(token_stream);
-
-    // Get the string value
-    let requested_field = string_literal.value();
-
-    let language = tree_sitter_c::language();
-    let found_id = language.field_id_for_name(&requested_field);
-
-    if let Some(found_id) = found_id {
-        let id_number: u16 = found_id;
-        quote! {
-            std::num::NonZeroU16::new(#id_number).unwrap()
-        }
-    } else {
-        quote_spanned!(
-            string_literal.span() =>
-            compile_error!("This is not a valid field in the tree-sitter-c grammar")
+    let Forwarded {
+        grammar,
+        lang_name,
+        tokens,
+        ..
+    } = parse_macro_input!(token_stream);
+
+    tree_sitter_proc_core::expand_field(&grammar.language(), tokens, lang_name).into()
+}
+
+/// Returns the field ID for a given field name as a plain `u16`.
+///
+/// Unlike `field!`, this macro expands to an integer literal, so it can be used
+/// in match patterns, for example against `TreeCursor::field_id`.
+///
+/// # Arguments
+///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`,
+///   followed by a comma. Defaults to the grammar selected in the calling
+///   crate's `[package.metadata.tree-sitter-proc]`, or `c`.
+/// * `kind_name` - An optional string literal representing the node kind the
+///   field must belong to, followed by a comma.
+/// * `field_name` - A string literal representing the field name.
+///
+/// # Returns
+///
+/// The field ID as a `u16` literal.
+///
+/// # Errors
+///
+/// Generates a compile-time error if the provided field name is not valid
+/// in the grammar, or if the given node kind can't have it. `field_id!` doesn't
+/// take the `typed` argument of `field!`, since it exists for raw field IDs:
+///
+/// ```compile_fail
+/// let body = tree_sitter_c_proc::field_id!(typed, "body");
+/// ```
+///
+/// # Examples
+///
+/// ```
+/// use tree_sitter_c_proc::{field, field_id};
+///
+/// let cursor_field_id = Some(field!("declarator").get());
+///
+/// match cursor_field_id {
+///     Some(field_id!("declarator")) => {}
+///     Some(field_id!("body")) => unreachable!(),
+///     _ => unreachable!(),
+/// }
+/// ```
+#[proc_macro]
+pub fn field_id(token_stream: TokenStream) -> TokenStream {
+    let Forwarded {
+        grammar,
+        typed,
+        lang_name,
+        tokens,
+    } = parse_macro_input!(token_stream);
+
+    // `field_id!` is for patterns on raw field IDs
+    if let Some(typed) = typed {
+        return syn::Error::new(
+            typed.span(),
+            "`field_id!` always expands to a `u16` literal; use `field!(typed, ...)` for a `FieldId`",
         )
+        .to_compile_error()
+        .into();
+    }
+
+    tree_sitter_proc_core::expand_field_id(&grammar.language(), tokens, lang_name).into()
+}
+
+/// Generates a `CKind` enum with one variant per node kind of the grammar.
+///
+/// Other grammars get an enum named after them, such as `CppKind` for
+/// `node_kind_enum!(cpp)`.
+///
+/// Unlike the bare `u16` returned by `kind!`, matching on a `CKind` is checked
+/// for exhaustiveness by the compiler. The enum is `#[repr(u16)]` and every
+/// discriminant is the ID `Node::kind_id` reports for that kind.
+///
+/// Named kinds become `UpperCamelCase` variants (`function_definition` becomes
+/// `FunctionDefinition`), and punctuation in anonymous tokens is spelled out
+/// (`"+="` becomes `PlusEq`). When two kinds map to the same variant name, the
+/// anonymous one gets a numeric suffix (`"NULL"` becomes `Null2`). The `ERROR`
+/// kind of unparseable input is included as `Error`.
+///
+/// The generated enum implements `TryFrom<u16>`, which accepts every symbol ID
+/// of aliased kinds and returns unknown IDs as the error, and `Display`. It also
+/// has `id()`, `as_str()` and `is_named()` methods.
+///
+/// # Arguments
+///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`.
+///   Defaults to the grammar selected in the calling crate's
+///   `[package.metadata.tree-sitter-proc]`, or `c`.
+///
+/// # Examples
+///
+/// ```
+/// use tree_sitter_c_proc::{kind, node_kind_enum};
+///
+/// node_kind_enum!();
+///
+/// let kind = CKind::try_from(kind!("if_statement")).unwrap();
+/// assert_eq!(kind, CKind::IfStatement);
+/// assert_eq!(kind.as_str(), "if_statement");
+/// assert!(kind.is_named());
+/// assert_eq!(CKind::LParen.to_string(), "(");
+/// ```
+#[proc_macro]
+pub fn node_kind_enum(token_stream: TokenStream) -> TokenStream {
+    let GrammarOnly(grammar) = parse_macro_input!(token_stream);
+
+    let dependencies = grammar.dependencies();
+    let kind_enum = kind_enum::expand(grammar);
+    quote!(#dependencies #kind_enum).into()
+}
+
+/// Generates typed wrappers for every named node kind of the grammar.
+///
+/// Each concrete named kind in the grammar's `node-types.json` becomes a
+/// zero-cost newtype around `tree_sitter::Node`, such as
+/// `FunctionDefinition<'tree>`, and each supertype becomes an enum of its
+/// subtypes, such as `Declarator<'tree>`. Casting from a `Node` goes through
+/// `TryFrom`, which checks the node kind ID against the IDs `kind!` computes
+/// and returns the node unchanged when it doesn't match.
+///
+/// Every field gets an accessor typed by the kinds the field can hold:
+///
+/// * a field that can hold several named kinds gets its own enum, named after
+///   the kind and the field, such as `DeclarationDeclarator<'tree>`;
+/// * a field that can hold anonymous tokens returns a plain `Node`;
+/// * an optional field returns an `Option`, a required field returns the value
+///   itself, and a field that can hold several nodes returns an iterator.
+///
+/// Accessors for required fields panic if syntax errors left the field empty
+/// or holding an unexpected kind. Field names that are Rust keywords become raw
+/// identifiers, such as `r#type()`.
+///
+/// The generated code refers to `::tree_sitter`, so the calling crate must
+/// depend on the `tree-sitter` crate. Invoke the macro inside a module to keep
+/// the generated types together.
+///
+/// # Arguments
+///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`.
+///   Defaults to the grammar selected in the calling crate's
+///   `[package.metadata.tree-sitter-proc]`, or `c`.
+///
+/// # Examples
+///
+/// ```
+/// mod ast {
+///     tree_sitter_c_proc::typed_nodes!();
+/// }
+///
+/// let mut parser = tree_sitter::Parser::new();
+/// parser.set_language(tree_sitter_c::language()).unwrap();
+/// let tree = parser.parse("int main(void) { return 0; }", None).unwrap();
+///
+/// let node = tree.root_node().child(0).unwrap();
+/// let function = ast::FunctionDefinition::try_from(node).unwrap();
+/// assert!(matches!(function.declarator(), ast::Declarator::FunctionDeclarator(_)));
+/// ```
+#[proc_macro]
+pub fn typed_nodes(token_stream: TokenStream) -> TokenStream {
+    let GrammarOnly(grammar) = parse_macro_input!(token_stream);
+
+    // The dependencies stay on error, so fixing the grammar's files rebuilds
+    let dependencies = grammar.dependencies();
+    let typed_nodes = typed_nodes::expand(
+        &grammar.language(),
+        grammar.lang_name(),
+        &grammar.node_types(),
+        Span::call_site(),
+    )
+    .unwrap_or_else(syn::Error::into_compile_error);
+    quote!(#dependencies #typed_nodes).into()
+}
+
+/// Generates a `CVisitor` trait and a `walk` function that drives it over a
+/// syntax tree.
+///
+/// Other grammars get a trait named after them, such as `CppVisitor` for
+/// `visitor!(cpp)`.
+///
+/// The trait has a `visit_<kind>` method for every named node kind, such as
+/// `visit_function_definition`, which does nothing by default, so a visitor
+/// only implements the methods for the kinds it cares about. Method names
+/// follow the variant names of `node_kind_enum!`, so the `ERROR` kind is
+/// visited by `visit_error`. Two further hooks, `enter` and `leave`, are called
+/// before and after every node, named or anonymous.
+///
+/// Every method returns a `VisitControl`: `Continue` descends into the node's
+/// children, while `SkipChildren` moves on to its next sibling. Returning
+/// `SkipChildren` from `enter` also skips the node's `visit_<kind>` method.
+/// `leave` is still called for nodes whose children were skipped.
+///
+/// `walk(&tree, &mut visitor)` visits the tree depth-first with a single
+/// `TreeCursor` rather than recursion, and dispatches each node to its method
+/// with a match on the node kind ID, which accepts every symbol ID of aliased
+/// kinds.
+///
+/// The generated code refers to `::tree_sitter`, so the calling crate must
+/// depend on the `tree-sitter` crate.
+///
+/// # Arguments
+///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`.
+///   Defaults to the grammar selected in the calling crate's
+///   `[package.metadata.tree-sitter-proc]`, or `c`.
+///
+/// # Examples
+///
+/// ```
+/// use tree_sitter::Node;
+/// use tree_sitter_c_proc::visitor;
+///
+/// visitor!();
+///
+/// #[derive(Default)]
+/// struct CallCounter {
+///     calls: usize,
+/// }
+///
+/// impl<'tree> CVisitor<'tree> for CallCounter {
+///     fn visit_call_expression(&mut self, _node: Node<'tree>) -> VisitControl {
+///         self.calls += 1;
+///         VisitControl::Continue
+///     }
+///
+///     fn visit_function_definition(&mut self, _node: Node<'tree>) -> VisitControl {
+///         VisitControl::SkipChildren
+///     }
+/// }
+///
+/// let mut parser = tree_sitter::Parser::new();
+/// parser.set_language(tree_sitter_c::language()).unwrap();
+/// let code = "int x = f(g(1)); int main(void) { return h(); }";
+/// let tree = parser.parse(code, None).unwrap();
+///
+/// let mut counter = CallCounter::default();
+/// walk(&tree, &mut counter);
+/// assert_eq!(counter.calls, 2);
+/// ```
+#[proc_macro]
+pub fn visitor(token_stream: TokenStream) -> TokenStream {
+    let GrammarOnly(grammar) = parse_macro_input!(token_stream);
+
+    let dependencies = grammar.dependencies();
+    let visitor = visitor::expand(grammar);
+    quote!(#dependencies #visitor).into()
+}
+
+/// Compiles a tree-sitter query against a grammar at compile time.
+///
+/// `Query::new` only reports a misspelled node kind, an unknown field or a
+/// syntax error when it runs. This macro runs it during compilation instead,
+/// so an invalid query never builds.
+///
+/// The macro expands to a `&'static` value that dereferences to a
+/// `tree_sitter::Query`, compiled on first use and shared afterwards. It also
+/// has a `u32` field for every capture holding the capture's index, named after
+/// the capture with characters that can't appear in identifiers replaced by
+/// `_` (`@function.name` becomes `function_name`) and Rust keywords written as
+/// raw identifiers (`@fn` becomes `r#fn`).
+///
+/// Preceded by `struct Name,`, optionally with a visibility, the macro instead
+/// defines a unit struct in item position whose associated `u32` constants are
+/// the capture indices, named in upper case (`Name::FUNCTION_NAME`, `Name::FN`),
+/// so they can be used in patterns and constant expressions. `Name::query()`
+/// returns the compiled query.
+///
+/// The generated code refers to `::tree_sitter` and to the grammar's crate,
+/// such as `::tree_sitter_c`, so the calling crate must depend on both.
+///
+/// # Arguments
+///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`,
+///   followed by a comma. Defaults to the grammar selected in the calling
+///   crate's `[package.metadata.tree-sitter-proc]`, or `c`.
+/// * `struct Name` - An optional struct to define, followed by a comma.
+/// * A string literal containing the query, usually a raw string.
+///
+/// # Returns
+///
+/// A `&'static` reference to the compiled query and its capture indices, or
+/// the struct `Name`.
+///
+/// # Errors
+///
+/// Fails to compile if the query is invalid. Unknown node kinds and fields
+/// come with suggestions, and the error names the line and column in the
+/// query. On nightly compilers, the error points at the offending part of the
+/// literal.
+///
+/// # Examples
+///
+/// ```
+/// use tree_sitter::QueryCursor;
+/// use tree_sitter_c_proc::query;
+///
+/// let calls = query!(r#"(call_expression function: (identifier) @function)"#);
+///
+/// let code = "int main(void) { puts(\"hi\"); return exit(0); }";
+/// let mut parser = tree_sitter::Parser::new();
+/// parser.set_language(tree_sitter_c::language()).unwrap();
+/// let tree = parser.parse(code, None).unwrap();
+///
+/// let mut cursor = QueryCursor::new();
+/// let names: Vec<&str> = cursor
+///     .captures(calls, tree.root_node(), code.as_bytes())
+///     .map(|(query_match, index)| query_match.captures[index])
+///     .filter(|capture| capture.index == calls.function)
+///     .map(|capture| capture.node.utf8_text(code.as_bytes()).unwrap())
+///     .collect();
+/// assert_eq!(names, ["puts", "exit"]);
+/// ```
+///
+/// The struct form, matching on the capture indices:
+///
+/// ```
+/// use tree_sitter::QueryCursor;
+/// use tree_sitter_c_proc::query;
+///
+/// query!(struct Calls, r#"(call_expression function: (identifier) @function arguments: (_) @arguments)"#);
+///
+/// let code = "int main(void) { return exit(0); }";
+/// let mut parser = tree_sitter::Parser::new();
+/// parser.set_language(tree_sitter_c::language()).unwrap();
+/// let tree = parser.parse(code, None).unwrap();
+///
+/// let mut cursor = QueryCursor::new();
+/// for (query_match, index) in cursor.captures(Calls::query(), tree.root_node(), code.as_bytes()) {
+///     let capture = query_match.captures[index];
+///     let text = capture.node.utf8_text(code.as_bytes()).unwrap();
+///     match capture.index {
+///         Calls::FUNCTION => assert_eq!(text, "exit"),
+///         Calls::ARGUMENTS => assert_eq!(text, "(0)"),
+///         _ => unreachable!(),
+///     }
+/// }
+/// ```
+#[proc_macro]
+pub fn query(token_stream: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(token_stream as query::QueryInput);
+    query::expand(input).into()
+}
+
+/// Derives a typed view of the matches of a tree-sitter query.
+///
+/// The query in the `#[query("...")]` attribute is compiled against the
+/// tree-sitter-c grammar at compile time, like `query!`, or against another
+/// grammar named before the query, as in `#[query(cpp, "...")]`. Every field
+/// of the struct is bound to the capture of the same name. Capture names that aren't
+/// identifiers are converted the way `query!` converts them, so a field named
+/// `function_name` holds the `@function.name` capture. The struct needs a
+/// lifetime parameter for the nodes it holds, and each field must be one of:
+///
+/// * `Node<'tree>` for a capture that holds exactly one node in every match;
+/// * `Option<Node<'tree>>` for a capture that can be missing, for example one
+///   quantified with `?` or only present in some patterns;
+/// * `Vec<Node<'tree>>` for a capture quantified with `*` or `+`.
+///
+/// Captures without a field are allowed, for example those only used by
+/// predicates.
+///
+/// The derive generates three associated functions: `query()` returns the
+/// compiled `&'static Query`, `from_match(&query_match)` builds the struct from
+/// a `QueryMatch`, and `matches(&mut cursor, node, text)` runs the query and
+/// yields the struct for every match.
+///
+/// The generated code refers to `::tree_sitter` and to the grammar's crate,
+/// such as `::tree_sitter_c`, so the calling crate must depend on both.
+///
+/// # Errors
+///
+/// Fails to compile if the query is invalid, if a field matches no capture, or
+/// if a capture can hold more nodes than its field can store.
+///
+/// # Examples
+///
+/// ```
+/// use tree_sitter::{Node, QueryCursor};
+/// use tree_sitter_c_proc::CQuery;
+///
+/// #[derive(CQuery)]
+/// #[query(r#"
+///     (function_definition
+///       declarator: (function_declarator declarator: (identifier) @name)
+///       body: (compound_statement (declaration)* @locals)) @def
+/// "#)]
+/// struct FnDef<'tree> {
+///     name: Node<'tree>,
+///     locals: Vec<Node<'tree>>,
+///     def: Node<'tree>,
+/// }
+///
+/// let code = "int add(int a, int b) { int sum; int unused; sum = a + b; return sum; }";
+/// let mut parser = tree_sitter::Parser::new();
+/// parser.set_language(tree_sitter_c::language()).unwrap();
+/// let tree = parser.parse(code, None).unwrap();
+///
+/// let mut cursor = QueryCursor::new();
+/// let definitions: Vec<FnDef> =
+///     FnDef::matches(&mut cursor, tree.root_node(), code.as_bytes()).collect();
+/// assert_eq!(definitions.len(), 1);
+/// assert_eq!(definitions[0].name.utf8_text(code.as_bytes()).unwrap(), "add");
+/// assert_eq!(definitions[0].locals.len(), 2);
+/// assert_eq!(definitions[0].def.kind(), "function_definition");
+/// ```
+#[proc_macro_derive(CQuery, attributes(query))]
+pub fn derive_c_query(token_stream: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(token_stream as DeriveInput);
+
+    query_derive::expand(&input).into()
+}
+
+/// Matches a node against structural patterns written like tree-sitter
+/// queries.
+///
+/// Each arm has a pattern in the S-expression syntax of tree-sitter queries,
+/// and the first arm whose pattern matches is evaluated. Patterns are checked
+/// against the grammar at compile time, and instead of running a
+/// `Query`, each pattern compiles to direct `kind_id` and `child_by_field_id`
+/// checks.
+///
+/// A pattern is one of:
+///
+/// * `(kind field: pattern ...)`: a node of a named kind, or of any subtype of
+///   a supertype such as `(_expression)`, whose fields match the nested
+///   patterns;
+/// * `(_ field: pattern ...)`: any named node whose fields match;
+/// * `"token"`: an anonymous token, such as an operator;
+/// * `_`: any node, for example to require that a field is present.
+///
+/// `!field` requires a field to be empty, and a pattern followed by `@name`
+/// binds the node it matched to the local `name: Node` in the arm. Unlike in
+/// queries, children can only be matched through their field, and a field that
+/// holds several nodes is matched on its first one.
+///
+/// The last arm must be `_ => ...`, which is evaluated when no pattern matches.
+/// The generated code refers to `::tree_sitter`, so the calling crate must
+/// depend on the `tree-sitter` crate.
+///
+/// # Arguments
+///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`,
+///   followed by a comma. Defaults to the grammar selected in the calling
+///   crate's `[package.metadata.tree-sitter-proc]`, or `c`.
+/// * An expression evaluating to a `tree_sitter::Node`.
+/// * The arms, in braces.
+///
+/// # Errors
+///
+/// Fails to compile if a pattern names a node kind, token or field that is not
+/// part of the grammar, or a field that the node kind can't have.
+///
+/// # Examples
+///
+/// ```
+/// use tree_sitter_c_proc::match_node;
+///
+/// let code = "int main(void) { puts(\"hi\"); return argc == 2; }";
+/// let mut parser = tree_sitter::Parser::new();
+/// parser.set_language(tree_sitter_c::language()).unwrap();
+/// let tree = parser.parse(code, None).unwrap();
+///
+/// let describe = |node: tree_sitter::Node| {
+///     match_node!(node, {
+///         (call_expression function: (identifier) @function arguments: _) => {
+///             format!("call to {}", function.utf8_text(code.as_bytes()).unwrap())
+///         }
+///         (binary_expression operator: "==" left: (_) @left) => {
+///             format!("comparison of {}", left.utf8_text(code.as_bytes()).unwrap())
+///         }
+///         _ => String::new(),
+///     })
+/// };
+///
+/// let body = tree.root_node().child(0).unwrap().child(2).unwrap();
+/// let call = body.named_child(0).unwrap().named_child(0).unwrap();
+/// let comparison = body.named_child(1).unwrap().named_child(0).unwrap();
+/// assert_eq!(describe(call), "call to puts");
+/// assert_eq!(describe(comparison), "comparison of argc");
+/// assert_eq!(describe(body), "");
+/// ```
+#[proc_macro]
+pub fn match_node(token_stream: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(token_stream as match_node::MatchNode);
+
+    match_node::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Records the symbol and field tables of the grammar the macros read.
+///
+/// The IDs `kind!` and the other macros expand to come from the grammar crate
+/// this proc macro was built with. If the calling crate links a different
+/// version of that grammar, every `match` on them is silently wrong. Call
+/// `verify_language` on the fingerprint once at startup, with the `Language`
+/// the program parses with, to catch that.
+///
+/// # Arguments
+///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`.
+///   Defaults to the grammar selected in the calling crate's
+///   `[package.metadata.tree-sitter-proc]`, or `c`.
+///
+/// # Returns
+///
+/// A `tree_sitter_c_proc_runtime::Fingerprint` constant expression, with a hash
+/// of the tables and the ID of every node kind and field. The calling crate
+/// needs `tree-sitter-c-proc-runtime` as a dependency.
+///
+/// # Examples
+///
+/// ```
+/// use tree_sitter_c_proc::grammar_fingerprint;
+/// use tree_sitter_c_proc_runtime::Fingerprint;
+///
+/// const C_GRAMMAR: Fingerprint = grammar_fingerprint!();
+///
+/// C_GRAMMAR
+///     .verify_language(&tree_sitter_c::language())
+///     .expect("tree-sitter-c has the IDs the macros expanded to");
+/// ```
+#[proc_macro]
+pub fn grammar_fingerprint(token_stream: TokenStream) -> TokenStream {
+    let GrammarOnly(grammar) = parse_macro_input!(token_stream);
+
+    fingerprint::expand(grammar).into()
+}
+
+/// Snapshots the symbol and field tables of the grammar for runtime lookups.
+///
+/// Names that only arrive at runtime, from a config file or a user's query,
+/// can't go through `kind!`, and `Language::id_for_node_kind` scans the whole
+/// symbol table across FFI on every call. The table this macro generates maps
+/// names to IDs through a perfect hash computed at compile time, and IDs to
+/// names through arrays, so every lookup is O(1), allocation-free and needs no
+/// `Language`.
+///
+/// # Arguments
+///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`.
+///   Defaults to the grammar selected in the calling crate's
+///   `[package.metadata.tree-sitter-proc]`, or `c`.
+///
+/// # Returns
+///
+/// A `tree_sitter_c_proc_runtime::SymbolTable` constant expression, with the
+/// same lookups as `tree_sitter::Language`, except that unknown names return
+/// `None` instead of 0. The calling crate needs `tree-sitter-c-proc-runtime` as
+/// a dependency.
+///
+/// # Examples
+///
+/// ```
+/// use tree_sitter_c_proc::{field_id, kind, kw, symbol_table};
+/// use tree_sitter_c_proc_runtime::SymbolTable;
+///
+/// static C_SYMBOLS: SymbolTable = symbol_table!();
+///
+/// assert_eq!(C_SYMBOLS.id_for_node_kind("if_statement", true), Some(kind!("if_statement")));
+/// assert_eq!(C_SYMBOLS.id_for_node_kind("if", false), Some(kw!("if")));
+/// assert_eq!(C_SYMBOLS.id_for_node_kind("if", true), None);
+/// assert_eq!(C_SYMBOLS.node_kind_for_id(kind!("if_statement")), Some("if_statement"));
+/// assert_eq!(C_SYMBOLS.field_id_for_name("body"), Some(field_id!("body")));
+/// ```
+#[proc_macro]
+pub fn symbol_table(token_stream: TokenStream) -> TokenStream {
+    let GrammarOnly(grammar) = parse_macro_input!(token_stream);
+
+    symbol_table::expand(grammar).into()
+}
+
+/// Returns a constant set of node kinds, for membership tests in O(1).
+///
+/// Where `kinds!` expands to a pattern that compares a kind ID against each
+/// alternative, the set stores one bit per symbol of the grammar. Sets combine
+/// in constants with `union`, `intersection` and `difference`, and traversal
+/// helpers can take them as a cheap predicate.
+///
+/// # Arguments
+///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`,
+///   followed by a comma. Defaults to the grammar selected in the calling
+///   crate's `[package.metadata.tree-sitter-proc]`, or `c`.
+/// * `kind_names` - A comma-separated list of string literals representing node
+///   kind names. Supertypes such as `_statement` stand for all of their
+///   concrete subtypes, and `"ERROR"` for the nodes tree-sitter reports for
+///   unparseable input.
+///
+/// # Returns
+///
+/// A `tree_sitter_c_proc_runtime::KindSet<N>` constant expression with every ID
+/// of the given kinds, including all IDs of aliased names. `N` is the grammar's
+/// symbol count divided by 64, rounded up, which is 6 for tree-sitter-c. The
+/// calling crate needs `tree-sitter-c-proc-runtime` as a dependency.
+///
+/// # Errors
+///
+/// Generates a compile-time error for every name that is neither a node kind
+/// nor a supertype in the grammar, each pointing at the offending literal.
+///
+/// # Examples
+///
+/// ```
+/// use tree_sitter_c_proc::{kind, kind_set};
+/// use tree_sitter_c_proc_runtime::KindSet;
+///
+/// const LOOPS: KindSet<6> = kind_set!("for_statement", "while_statement", "do_statement");
+/// const STATEMENTS: KindSet<6> = kind_set!("_statement");
+/// const OTHER_STATEMENTS: KindSet<6> = STATEMENTS.difference(LOOPS);
+///
+/// assert!(LOOPS.contains(kind!("while_statement")));
+/// assert!(OTHER_STATEMENTS.contains(kind!("if_statement")));
+/// assert!(!OTHER_STATEMENTS.contains(kind!("for_statement")));
+/// assert_eq!(LOOPS.iter().count(), 3);
+/// ```
+#[proc_macro]
+pub fn kind_set(token_stream: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(token_stream as kind_set::KindSetInput);
+
+    kind_set::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Follows a path of fields from a node, checked against the grammar.
+///
+/// Nested `child_by_field_id` lookups compile however wrong the path is, and a
+/// field the node in front of it can never have just returns `None` at runtime.
+/// This macro knows which node kinds can occur at every step from
+/// `node-types.json`, and fails to compile if none of them can have the next
+/// field. The kinds before the first step are unknown, unless the path starts
+/// with a filter.
+///
+/// Steps are separated by `/`:
+///
+/// * `field` - The first child in the field.
+/// * `*` - The first named child, in a field or not.
+/// * `[kind | kind]` after a step - The first of those children whose kind is
+///   one of these. Supertypes such as `_declarator` match all of their concrete
+///   subtypes. At the start of the path, the filter checks the node itself.
+///
+/// The generated code refers to `::tree_sitter`, so the calling crate must
+/// depend on the `tree-sitter` crate.
+///
+/// # Arguments
+///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`,
+///   followed by a comma. Defaults to the grammar selected in the calling
+///   crate's `[package.metadata.tree-sitter-proc]`, or `c`.
+/// * An expression evaluating to a `tree_sitter::Node`, followed by `=>`.
+/// * The path.
+///
+/// # Returns
+///
+/// An `Option<tree_sitter::Node>` expression: the node at the end of the path,
+/// or `None` as soon as a step finds nothing, like a chain of `?`.
+///
+/// # Errors
+///
+/// Fails to compile if a step names a field or kind that is not part of the
+/// grammar, a field that none of the nodes before it can have, or a kind that
+/// can't occur where it filters.
+///
+/// # Examples
+///
+/// ```
+/// use tree_sitter_c_proc::field_path;
+///
+/// let code = "struct point *origin(void);";
+/// let mut parser = tree_sitter::Parser::new();
+/// parser.set_language(tree_sitter_c::language()).unwrap();
+/// let tree = parser.parse(code, None).unwrap();
+/// let declaration = tree.root_node().child(0).unwrap();
+///
+/// let text = |node: tree_sitter::Node| node.utf8_text(code.as_bytes()).unwrap();
+///
+/// let name = field_path!(declaration => [declaration] declarator / declarator / declarator);
+/// assert_eq!(name.map(text), Some("origin"));
+///
+/// let tag = field_path!(declaration => type[struct_specifier] / name);
+/// assert_eq!(tag.map(text), Some("point"));
+///
+/// let parameter = field_path!(declaration => declarator / *[function_declarator] / parameters / *);
+/// assert_eq!(parameter.map(|node| node.kind()), Some("parameter_declaration"));
+///
+/// // The declaration has no initializer
+/// let value = field_path!(declaration => declarator[init_declarator] / value);
+/// assert_eq!(value, None);
+/// ```
+///
+/// A declarator has no `name` field, so this is a compile error:
+///
+/// ```compile_fail
+/// # let node: tree_sitter::Node = unimplemented!();
+/// let name = tree_sitter_c_proc::field_path!(node => declarator / declarator / name);
+/// ```
+#[proc_macro]
+pub fn field_path(token_stream: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(token_stream as field_path::FieldPath);
+
+    field_path::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Makes cargo rebuild the crate when the grammar the macros read changes.
+///
+/// The macros read a grammar from disk when `TREE_SITTER_C_GRAMMAR_DIR` (or
+/// `TREE_SITTER_CPP_GRAMMAR_DIR`, `TREE_SITTER_RUST_GRAMMAR_DIR`) points at a
+/// grammar's directory, or when the calling crate's manifest sets
+/// `node-types` in `[package.metadata.tree-sitter-proc]`. Cargo doesn't know
+/// about those files, so the expansions that can contain items, such as
+/// `node_kind_enum!`, `query!` and `match_node!`, also tell it to rebuild when
+/// they change or when the variable is set or changed.
+///
+/// `kind!`, `kinds!`, `kw!`, `kws!`, `supertype!`, `field!` and `field_id!` can't, so a
+/// crate that only uses those invokes this macro once, at the top of its
+/// `lib.rs` or `main.rs`.
+///
+/// # Arguments
+///
+/// * `grammar` - An optional identifier selecting the grammar, such as `cpp`.
+///   Defaults to the grammar selected in the calling crate's
+///   `[package.metadata.tree-sitter-proc]`, or `c`.
+///
+/// # Examples
+///
+/// ```
+/// tree_sitter_c_proc::track_grammar!();
+///
+/// fn is_if(kind_id: u16) -> bool {
+///     kind_id == tree_sitter_c_proc::kind!("if_statement")
+/// }
+/// ```
+#[proc_macro]
+pub fn track_grammar(token_stream: TokenStream) -> TokenStream {
+    let GrammarOnly(grammar) = parse_macro_input!(token_stream);
+
+    grammar.dependencies().into()
+}
+
+/// Input of the macros that `tree_sitter_proc_core` expands: the tokens it
+/// parses, optionally preceded by the grammar and by `typed`.
+struct Forwarded {
+    grammar: &'static Grammar,
+    /// The `typed` argument, which makes the IDs `KindId`, `TokenId` and
+    /// `FieldId` values instead of bare integers.
+    typed: Option<Ident>,
+    lang_name: LangName<'static>,
+    tokens: proc_macro2::TokenStream,
+}
+
+impl Parse for Forwarded {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // `typed` doesn't name a grammar, so it can start the input on its own
+        let grammar = if peek_typed(input) {
+            grammar::default_grammar(input.span())?
+        } else {
+            grammar::parse_leading(input)?
+        };
+
+        let typed = if peek_typed(input) {
+            let typed = input.parse()?;
+            input.parse::<Token![,]>()?;
+            Some(typed)
+        } else {
+            None
+        };
+
+        let lang_name = match typed {
+            Some(_) => grammar
+                .lang_name()
+                .with_typed_ids("::tree_sitter_c_proc_runtime"),
+            None => grammar.lang_name(),
+        };
+
+        Ok(Forwarded {
+            grammar,
+            typed,
+            lang_name,
+            tokens: input.parse()?,
+        })
     }
-    .into()
+}
+
+/// Returns `true` if the input continues with the `typed,` argument.
+fn peek_typed(input: ParseStream) -> bool {
+    input.peek2(Token![,])
+        && input
+            .fork()
+            .parse::<Ident>()
+            .is_ok_and(|ident| ident == "typed")
 }
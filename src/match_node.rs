@@ -0,0 +1,400 @@
+//! Parsing and code generation for `match_node!`.
+//!
+//! Patterns use the S-expression syntax of tree-sitter queries, but instead of
+//! being compiled into a `Query` at runtime, every pattern becomes a chain of
+//! `kind_id` and `child_by_field_id` checks.
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::{braced, parenthesized, Expr, LitStr, Token};
+use tree_sitter::Language;
+use tree_sitter_proc_core::diagnostics;
+use tree_sitter_proc_core::node_types::NodeTypes;
+use tree_sitter_proc_core::{symbols, LangName};
+
+use crate::grammar::{self, Grammar};
+
+/// Input of `match_node!`: the grammar, the node to match and the arms.
+pub(crate) struct MatchNode {
+    grammar: &'static Grammar,
+    node: Expr,
+    arms: Vec<Arm>,
+}
+
+struct Arm {
+    pattern: Pattern,
+    body: Expr,
+}
+
+/// A pattern together with the local its node is captured into, if any.
+struct Pattern {
+    kind: PatternKind,
+    capture: Option<Ident>,
+}
+
+enum PatternKind {
+    /// `_`: any node.
+    Wildcard(Span),
+    /// `"token"`: an anonymous token.
+    Token(LitStr),
+    /// `(kind field: pattern !field)`, or `(_ ...)` for any named node.
+    Node {
+        kind: Option<Ident>,
+        span: Span,
+        fields: Vec<FieldPattern>,
+    },
+}
+
+enum FieldPattern {
+    /// `field: pattern`: the field holds a node matching the pattern.
+    Present { field: Ident, pattern: Pattern },
+    /// `!field`: the field is empty.
+    Absent { field: Ident },
+}
+
+impl Parse for MatchNode {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // The grammar is optional, and an identifier followed by a comma is
+        // also how the node usually starts: only a second expression before
+        // the arms makes the first one the grammar.
+        let first: Expr = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let (grammar, node) = if input.peek(syn::token::Brace) {
+            (grammar::default_grammar(input.span())?, first)
+        } else {
+            let Expr::Path(path) = &first else {
+                return Err(syn::Error::new_spanned(first, "expected a grammar name"));
+            };
+            let grammar = grammar::lookup(path.path.require_ident()?)?;
+            let node = input.parse()?;
+            input.parse::<Token![,]>()?;
+            (grammar, node)
+        };
+
+        let content;
+        braced!(content in input);
+        let mut arms = Vec::new();
+        while !content.is_empty() {
+            let pattern = content.parse()?;
+            content.parse::<Token![=>]>()?;
+            let body = content.call(Expr::parse_with_earlier_boundary_rule)?;
+            let is_block = matches!(body, Expr::Block(_));
+            arms.push(Arm { pattern, body });
+
+            if content.is_empty() {
+                break;
+            }
+            if is_block {
+                content.parse::<Option<Token![,]>>()?;
+            } else {
+                content.parse::<Token![,]>()?;
+            }
+        }
+
+        input.parse::<Option<Token![,]>>()?;
+
+        Ok(MatchNode {
+            grammar,
+            node,
+            arms,
+        })
+    }
+}
+
+impl Parse for Pattern {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let kind = if input.peek(Token![_]) {
+            let underscore = input.parse::<Token![_]>()?;
+            PatternKind::Wildcard(underscore.span)
+        } else if input.peek(LitStr) {
+            PatternKind::Token(input.parse()?)
+        } else if input.peek(syn::token::Paren) {
+            let content;
+            let paren = parenthesized!(content in input);
+            let kind = if content.peek(Token![_]) {
+                content.parse::<Token![_]>()?;
+                None
+            } else {
+                Some(content.call(Ident::parse_any)?)
+            };
+
+            let mut fields = Vec::new();
+            while !content.is_empty() {
+                if content.peek(Token![!]) {
+                    content.parse::<Token![!]>()?;
+                    let field = content.call(Ident::parse_any)?;
+                    fields.push(FieldPattern::Absent { field });
+                } else if content.peek(Ident::peek_any) && content.peek2(Token![:]) {
+                    let field = content.call(Ident::parse_any)?;
+                    content.parse::<Token![:]>()?;
+                    let pattern = content.parse()?;
+                    fields.push(FieldPattern::Present { field, pattern });
+                } else {
+                    return Err(content.error(
+                        "expected `field: pattern` or `!field`; match_node! only matches \
+                         children through their field",
+                    ));
+                }
+            }
+
+            PatternKind::Node {
+                kind,
+                span: paren.span.join(),
+                fields,
+            }
+        } else {
+            return Err(input.error("expected `(kind ...)`, `\"token\"` or `_`"));
+        };
+
+        let capture = if input.peek(Token![@]) {
+            input.parse::<Token![@]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+
+        Ok(Pattern { kind, capture })
+    }
+}
+
+/// Validates the patterns against the grammar and generates the checks.
+pub(crate) fn expand(input: MatchNode) -> syn::Result<TokenStream> {
+    let language = input.grammar.language();
+    let node_types = input.grammar.node_types();
+    let generator = Generator {
+        lang_name: input.grammar.lang_name(),
+        language: &language,
+        node_types: &node_types,
+    };
+
+    let node = Ident::new("node", Span::mixed_site());
+    let match_label = syn::Lifetime::new("'match_node", Span::mixed_site());
+    let arm_label = syn::Lifetime::new("'arm", Span::mixed_site());
+    let scrutinee = &input.node;
+
+    let Some((fallback, arms)) = input.arms.split_last() else {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            "match_node! needs at least a final `_ => ...` arm",
+        ));
+    };
+    if !matches!(fallback.pattern.kind, PatternKind::Wildcard(_))
+        || fallback.pattern.capture.is_some()
+    {
+        return Err(syn::Error::new(
+            pattern_span(&fallback.pattern),
+            "the last arm of match_node! must be `_ => ...`, so that every node is matched",
+        ));
+    }
+
+    let mut checked_arms = Vec::new();
+    for arm in arms {
+        if let PatternKind::Wildcard(span) = arm.pattern.kind {
+            return Err(syn::Error::new(
+                span,
+                "`_` matches every node, so the arms after it are unreachable",
+            ));
+        }
+
+        let mut checks = Vec::new();
+        let mut captures = Vec::new();
+        let mut counter = 0;
+        generator.pattern(
+            &arm.pattern,
+            &node,
+            &mut checks,
+            &mut captures,
+            &mut counter,
+        )?;
+
+        // Captures are bound in the arm, so they must be unique per pattern.
+        for (index, (capture, _)) in captures.iter().enumerate() {
+            if captures[..index].iter().any(|(other, _)| other == capture) {
+                return Err(syn::Error::new(
+                    capture.span(),
+                    format!("the @{capture} capture is bound more than once in this pattern"),
+                ));
+            }
+        }
+
+        let names = captures.iter().map(|(capture, _)| capture);
+        let values = captures.iter().map(|(_, value)| value);
+        let body = &arm.body;
+        checked_arms.push(quote! {
+            if let ::core::option::Option::Some((#(#names,)*)) = #arm_label: {
+                #(#checks)*
+                ::core::option::Option::Some((#(#values,)*))
+            } {
+                break #match_label #body;
+            }
+        });
+    }
+
+    let fallback_body = &fallback.body;
+    let dependencies = input.grammar.dependencies();
+    Ok(quote! {
+        {
+            #dependencies
+            let #node: ::tree_sitter::Node<'_> = #scrutinee;
+            #match_label: {
+                #(#checked_arms)*
+                #fallback_body
+            }
+        }
+    })
+}
+
+struct Generator<'a> {
+    lang_name: LangName<'static>,
+    language: &'a Language,
+    node_types: &'a NodeTypes,
+}
+
+impl Generator<'_> {
+    /// Appends the checks that `pattern` matches the node in the local `node`,
+    /// and the locals it captures.
+    fn pattern(
+        &self,
+        pattern: &Pattern,
+        node: &Ident,
+        checks: &mut Vec<TokenStream>,
+        captures: &mut Vec<(Ident, Ident)>,
+        counter: &mut usize,
+    ) -> syn::Result<()> {
+        let arm_label = syn::Lifetime::new("'arm", Span::mixed_site());
+        let mismatch = quote!(break #arm_label ::core::option::Option::None;);
+
+        match &pattern.kind {
+            PatternKind::Wildcard(_) => {}
+            PatternKind::Token(literal) => {
+                let ids = symbols::ids_for_node_kind(self.language, &literal.value(), false);
+                if ids.is_empty() {
+                    let message = diagnostics::unknown_pattern_kind(
+                        self.language,
+                        self.lang_name,
+                        &literal.value(),
+                        false,
+                    );
+                    return Err(syn::Error::new(literal.span(), message));
+                }
+                checks.push(quote! {
+                    if !::core::matches!(#node.kind_id(), #(#ids)|*) {
+                        #mismatch
+                    }
+                });
+            }
+            PatternKind::Node { kind, fields, .. } => {
+                let kind_name = kind.as_ref().map(|kind| kind.unraw().to_string());
+                match (&kind_name, kind) {
+                    (Some(name), Some(kind)) => {
+                        let ids = self.kind_ids(name, kind)?;
+                        checks.push(quote! {
+                            if !::core::matches!(#node.kind_id(), #(#ids)|*) {
+                                #mismatch
+                            }
+                        });
+                    }
+                    _ => checks.push(quote! {
+                        if !#node.is_named() {
+                            #mismatch
+                        }
+                    }),
+                }
+
+                for field_pattern in fields {
+                    let (FieldPattern::Present { field, .. } | FieldPattern::Absent { field }) =
+                        field_pattern;
+                    let field_id = self.field_id(kind_name.as_deref(), field)?;
+
+                    match field_pattern {
+                        FieldPattern::Absent { .. } => checks.push(quote! {
+                            if #node.child_by_field_id(#field_id).is_some() {
+                                #mismatch
+                            }
+                        }),
+                        FieldPattern::Present { pattern, .. } => {
+                            *counter += 1;
+                            let child =
+                                format_ident!("child_{}", counter, span = Span::mixed_site());
+                            checks.push(quote! {
+                                let ::core::option::Option::Some(#child) =
+                                    #node.child_by_field_id(#field_id)
+                                else {
+                                    #mismatch
+                                };
+                            });
+                            self.pattern(pattern, &child, checks, captures, counter)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(capture) = &pattern.capture {
+            captures.push((capture.clone(), node.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the symbol IDs a named kind or supertype in a pattern matches.
+    fn kind_ids(&self, name: &str, kind: &Ident) -> syn::Result<Vec<u16>> {
+        // Unparseable input is reported as `ERROR`, which isn't in the symbol
+        // table.
+        if name == "ERROR" {
+            return Ok(vec![u16::MAX]);
+        }
+
+        let ids = symbols::ids_for_node_kind(self.language, name, true);
+        if !ids.is_empty() {
+            return Ok(ids);
+        }
+
+        match self.node_types.concrete_subtypes(name) {
+            Some(subtypes) => Ok(subtypes
+                .iter()
+                .flat_map(|subtype| {
+                    symbols::ids_for_node_kind(self.language, &subtype.kind, subtype.named)
+                })
+                .collect()),
+            None => Err(syn::Error::new(
+                kind.span(),
+                diagnostics::unknown_pattern_kind(self.language, self.lang_name, name, true),
+            )),
+        }
+    }
+
+    /// Returns the ID of `field`, checking that nodes of kind `kind` can have
+    /// it when the kind is concrete.
+    fn field_id(&self, kind: Option<&str>, field: &Ident) -> syn::Result<u16> {
+        let field_name = field.unraw().to_string();
+        let Some(field_id) = self.language.field_id_for_name(&field_name) else {
+            return Err(syn::Error::new(
+                field.span(),
+                diagnostics::unknown_field(self.language, self.lang_name, &field_name),
+            ));
+        };
+
+        if let Some(kind) = kind.filter(|kind| !self.node_types.is_supertype(kind)) {
+            let fields = self.node_types.field_names(kind).unwrap_or_default();
+            if !fields.contains(&field_name.as_str()) {
+                return Err(syn::Error::new(
+                    field.span(),
+                    diagnostics::field_not_in_kind(self.lang_name, kind, &field_name, &fields),
+                ));
+            }
+        }
+
+        Ok(field_id)
+    }
+}
+
+fn pattern_span(pattern: &Pattern) -> Span {
+    match &pattern.kind {
+        PatternKind::Wildcard(span) => *span,
+        PatternKind::Token(literal) => literal.span(),
+        PatternKind::Node { span, .. } => *span,
+    }
+}
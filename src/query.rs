@@ -0,0 +1,249 @@
+//! Code generation for `query!`.
+//!
+//! The query is compiled with `tree_sitter::Query::new` inside the macro, so
+//! every error `Query::new` would report at runtime becomes a compile error
+//! instead.
+
+use std::ops::Range;
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::{LitStr, Token, Visibility};
+use tree_sitter::{Query, QueryError, QueryErrorKind};
+use tree_sitter_proc_core::diagnostics;
+use tree_sitter_proc_core::naming;
+
+use crate::grammar::{self, Grammar};
+
+/// Input of `query!`: the grammar, the name of the struct to define, if any,
+/// and the query.
+pub(crate) struct QueryInput {
+    grammar: &'static Grammar,
+    item: Option<(Visibility, Ident)>,
+    literal: LitStr,
+}
+
+impl Parse for QueryInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let grammar = grammar::parse_leading(input)?;
+
+        let item = if input.peek(Token![struct]) || input.peek(Token![pub]) {
+            let visibility = input.parse()?;
+            input.parse::<Token![struct]>()?;
+            let name = input.parse()?;
+            input.parse::<Token![,]>()?;
+            Some((visibility, name))
+        } else {
+            None
+        };
+
+        let literal = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(QueryInput {
+            grammar,
+            item,
+            literal,
+        })
+    }
+}
+
+/// Validates the query and generates either its lazily compiled static, or a
+/// struct with a constant for the index of every capture.
+pub(crate) fn expand(input: QueryInput) -> TokenStream {
+    let QueryInput {
+        grammar,
+        item,
+        literal,
+    } = input;
+    let literal = &literal;
+    let query = match compile(grammar, literal) {
+        Ok(query) => query,
+        Err(error) => return error,
+    };
+
+    if let Some((visibility, name)) = item {
+        return expand_struct(grammar, literal, &query, &visibility, &name);
+    }
+
+    let fields = capture_fields(&query);
+    let docs = query
+        .capture_names()
+        .iter()
+        .map(|name| format!("The index of the `@{name}` capture."));
+    let indices = 0..fields.len() as u32;
+    let lazy_query = lazy_query(grammar, &literal.value());
+    let dependencies = grammar.dependencies();
+
+    quote! {
+        {
+            #dependencies
+
+            /// A query validated at compile time, with the index of every
+            /// capture.
+            #[allow(dead_code)]
+            struct Captures {
+                #(
+                    #[doc = #docs]
+                    #fields: u32,
+                )*
+            }
+
+            impl ::core::ops::Deref for Captures {
+                type Target = ::tree_sitter::Query;
+
+                fn deref(&self) -> &::tree_sitter::Query {
+                    #lazy_query
+                }
+            }
+
+            static CAPTURES: Captures = Captures {
+                #(#fields: #indices,)*
+            };
+
+            &CAPTURES
+        }
+    }
+}
+
+/// Defines the struct `name`, whose associated constants are the capture
+/// indices of `query`, so that they work in patterns and constant expressions.
+fn expand_struct(
+    grammar: &Grammar,
+    literal: &LitStr,
+    query: &Query,
+    visibility: &Visibility,
+    name: &Ident,
+) -> TokenStream {
+    let constants = capture_fields(query).into_iter().map(|field| {
+        let constant = field.unraw().to_string().to_ascii_uppercase();
+        Ident::new(&constant, field.span())
+    });
+    let docs = query
+        .capture_names()
+        .iter()
+        .map(|name| format!("The index of the `@{name}` capture."));
+    let indices = 0..query.capture_names().len() as u32;
+    let lazy_query = lazy_query(grammar, &literal.value());
+    let dependencies = grammar.dependencies();
+
+    quote! {
+        #dependencies
+
+        /// A query validated at compile time, with the index of every capture.
+        #visibility struct #name;
+
+        #[allow(dead_code)]
+        impl #name {
+            #(
+                #[doc = #docs]
+                pub const #constants: u32 = #indices;
+            )*
+
+            /// Returns the query, compiled on first use.
+            pub fn query() -> &'static ::tree_sitter::Query {
+                #lazy_query
+            }
+        }
+    }
+}
+
+/// Compiles the query in `literal`, turning a `QueryError` into a
+/// `compile_error!` spanned to the offending part of the literal.
+pub(crate) fn compile(grammar: &Grammar, literal: &LitStr) -> Result<Query, TokenStream> {
+    let source = literal.value();
+    let language = grammar.language();
+    Query::new(language, &source).map_err(|error| {
+        let message = diagnostics::query_error(&language, grammar.lang_name(), &source, &error);
+        let span = literal_span(literal, &source, error_range(&source, &error));
+        quote_spanned!(span => compile_error!(#message))
+    })
+}
+
+/// Generates an expression that evaluates to a `&'static tree_sitter::Query`,
+/// compiling `source` on first use.
+pub(crate) fn lazy_query(grammar: &Grammar, source: &str) -> TokenStream {
+    let grammar_crate = grammar.crate_path();
+    quote! {
+        static QUERY: ::std::sync::OnceLock<::tree_sitter::Query> = ::std::sync::OnceLock::new();
+        QUERY.get_or_init(|| {
+            ::tree_sitter::Query::new(#grammar_crate::language(), #source)
+                .expect("the query was validated at compile time")
+        })
+    }
+}
+
+/// Returns the Rust identifier for every capture of `query`, by capture index.
+pub(crate) fn capture_fields(query: &Query) -> Vec<Ident> {
+    let mut taken = Vec::new();
+    query
+        .capture_names()
+        .iter()
+        .map(|name| capture_field(name, &mut taken))
+        .collect()
+}
+
+/// Returns a unique field name for a capture, replacing the characters that
+/// can't appear in identifiers, such as the `.` in `@function.name`.
+fn capture_field(name: &str, taken: &mut Vec<String>) -> Ident {
+    let mut base: String = name
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() {
+                character
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if base.is_empty() || base.starts_with(|character: char| character.is_ascii_digit()) {
+        base.insert(0, '_');
+    }
+
+    let mut field = base.clone();
+    let mut suffix = 2;
+    while taken.contains(&field) {
+        field = format!("{base}{suffix}");
+        suffix += 1;
+    }
+    taken.push(field.clone());
+    naming::snake_case_ident(&field)
+}
+
+/// Returns the byte range of the query source an error is about.
+fn error_range(source: &str, error: &QueryError) -> Range<usize> {
+    let start = error.offset.min(source.len());
+    let length = match error.kind {
+        QueryErrorKind::NodeType | QueryErrorKind::Field | QueryErrorKind::Capture => {
+            error.message.len()
+        }
+        _ => 1,
+    };
+    start..(start + length).min(source.len())
+}
+
+/// Returns the span of `range` within the query literal.
+///
+/// Spans inside a literal are only available on nightly compilers and only
+/// when the literal has no escape sequences, so this falls back to the span of
+/// the whole literal.
+fn literal_span(literal: &LitStr, source: &str, range: Range<usize>) -> Span {
+    let token = literal.token();
+    let text = token.to_string();
+
+    // Offset of the string contents within the token: `"` or `r#"`
+    let prefix = match text.strip_prefix('r') {
+        Some(raw) => raw.find('"').map(|quote| quote + 2),
+        None => Some(1),
+    };
+    let Some(prefix) =
+        prefix.filter(|&prefix| text.get(prefix..prefix + source.len()) == Some(source))
+    else {
+        return literal.span();
+    };
+
+    token
+        .subspan(prefix + range.start..prefix + range.end)
+        .unwrap_or_else(|| literal.span())
+}
@@ -0,0 +1,253 @@
+//! Code generation for `#[derive(CQuery)]`.
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields, GenericArgument, LitStr, PathArguments, Token, Type};
+use tree_sitter::{CaptureQuantifier, Query};
+use tree_sitter_proc_core::diagnostics;
+
+use crate::grammar::{self, Grammar};
+use crate::query;
+
+/// How a struct field holds the nodes of its capture.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Arity {
+    /// `Node<'tree>`: exactly one node in every match.
+    One,
+    /// `Option<Node<'tree>>`: at most one node.
+    Optional,
+    /// `Vec<Node<'tree>>`: any number of nodes.
+    Many,
+}
+
+/// Generates the query accessor and the typed match iterator for a struct.
+pub(crate) fn expand(input: &DeriveInput) -> TokenStream {
+    match try_expand(input) {
+        Ok(tokens) => tokens,
+        Err(error) => error,
+    }
+}
+
+fn try_expand(input: &DeriveInput) -> Result<TokenStream, TokenStream> {
+    let ident = &input.ident;
+
+    let Some(QueryAttribute { grammar, literal }) = query_attribute(input)? else {
+        let message = "`#[derive(CQuery)]` needs a `#[query(\"...\")]` attribute with the query";
+        return Err(quote_spanned!(ident.span() => compile_error!(#message);));
+    };
+    let query = query::compile(grammar, &literal).map_err(|error| quote!(#error;))?;
+    let captures = query::capture_fields(&query);
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(error_at(
+                    ident,
+                    "`CQuery` can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => return Err(error_at(ident, "`CQuery` can only be derived for structs")),
+    };
+
+    let Some(tree_lifetime) = input
+        .generics
+        .lifetimes()
+        .next()
+        .map(|param| &param.lifetime)
+    else {
+        return Err(error_at(
+            ident,
+            "`CQuery` structs need a lifetime parameter for the nodes they hold, such as `'tree`",
+        ));
+    };
+
+    let mut field_inits = Vec::new();
+    for field in fields {
+        let field_ident = field.ident.as_ref().expect("named fields have identifiers");
+        let field_name = field_ident.unraw().to_string();
+
+        let Some(index) = captures
+            .iter()
+            .position(|capture| capture.unraw() == field_name)
+        else {
+            let capture_names: Vec<&str> =
+                query.capture_names().iter().map(String::as_str).collect();
+            let message = diagnostics::unknown_capture(&field_name, &capture_names);
+            return Err(error_at(field_ident, &message));
+        };
+
+        let Some(arity) = field_arity(&field.ty) else {
+            return Err(error_at(
+                &field.ty,
+                "`CQuery` fields must be `Node<'tree>`, `Option<Node<'tree>>` or `Vec<Node<'tree>>`",
+            ));
+        };
+        check_quantifiers(&query, index, arity).map_err(|message| error_at(&field.ty, &message))?;
+
+        let index = index as u32;
+        let captured = quote! {
+            query_match
+                .captures
+                .iter()
+                .filter(|capture| capture.index == #index)
+                .map(|capture| capture.node)
+        };
+        let missing = format!(
+            "the @{} capture is in every match",
+            query.capture_names()[index as usize]
+        );
+        let init = match arity {
+            Arity::One => quote!(#captured.next().expect(#missing)),
+            Arity::Optional => quote!(#captured.next()),
+            Arity::Many => quote!(#captured.collect()),
+        };
+        field_inits.push(quote!(#field_ident: #init));
+    }
+
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let lazy_query = query::lazy_query(grammar, &literal.value());
+    let dependencies = grammar.dependencies();
+
+    Ok(quote! {
+        #dependencies
+
+        impl #impl_generics #ident #type_generics #where_clause {
+            /// Returns the query this struct was derived from, compiled on
+            /// first use.
+            pub fn query() -> &'static ::tree_sitter::Query {
+                #lazy_query
+            }
+
+            /// Builds the struct from the captures of a match of
+            /// [`Self::query`].
+            pub fn from_match(
+                query_match: &::tree_sitter::QueryMatch<'_, #tree_lifetime>,
+            ) -> Self {
+                #ident {
+                    #(#field_inits,)*
+                }
+            }
+
+            /// Runs the query on `node` and yields the struct for every match.
+            pub fn matches<'cursor, Text>(
+                cursor: &'cursor mut ::tree_sitter::QueryCursor,
+                node: ::tree_sitter::Node<#tree_lifetime>,
+                text_provider: Text,
+            ) -> impl ::core::iter::Iterator<Item = Self> + 'cursor
+            where
+                Text: ::tree_sitter::TextProvider<'cursor> + 'cursor,
+                #tree_lifetime: 'cursor,
+                Self: 'cursor,
+            {
+                cursor
+                    .matches(Self::query(), node, text_provider)
+                    .map(|query_match| Self::from_match(&query_match))
+            }
+        }
+    })
+}
+
+/// The arguments of the `#[query(...)]` attribute: an optional grammar and the
+/// query.
+struct QueryAttribute {
+    grammar: &'static Grammar,
+    literal: LitStr,
+}
+
+impl Parse for QueryAttribute {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let grammar = grammar::parse_leading(input)?;
+        let literal = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+        Ok(QueryAttribute { grammar, literal })
+    }
+}
+
+/// Returns the arguments of the `#[query("...")]` attribute, if any.
+fn query_attribute(input: &DeriveInput) -> Result<Option<QueryAttribute>, TokenStream> {
+    let Some(attribute) = input
+        .attrs
+        .iter()
+        .find(|attribute| attribute.path().is_ident("query"))
+    else {
+        return Ok(None);
+    };
+
+    attribute
+        .parse_args()
+        .map(Some)
+        .map_err(|error| error.to_compile_error())
+}
+
+/// Returns how a field of type `ty` holds its nodes, or `None` if it isn't a
+/// node, an optional node or a vector of nodes.
+fn field_arity(ty: &Type) -> Option<Arity> {
+    let (name, inner) = last_segment(ty)?;
+    match (name.as_str(), inner) {
+        ("Node", _) => Some(Arity::One),
+        ("Option", Some(inner)) if is_node(inner) => Some(Arity::Optional),
+        ("Vec", Some(inner)) if is_node(inner) => Some(Arity::Many),
+        _ => None,
+    }
+}
+
+fn is_node(ty: &Type) -> bool {
+    matches!(last_segment(ty), Some((name, _)) if name == "Node")
+}
+
+/// Returns the name of the last path segment of `ty` and its first type
+/// argument, if any.
+fn last_segment(ty: &Type) -> Option<(String, Option<&Type>)> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let inner = match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => {
+            arguments.args.iter().find_map(|argument| match argument {
+                GenericArgument::Type(inner) => Some(inner),
+                _ => None,
+            })
+        }
+        _ => None,
+    };
+    Some((segment.ident.to_string(), inner))
+}
+
+/// Checks that the capture at `index` holds as many nodes, in every pattern of
+/// the query, as a field with `arity` can store.
+fn check_quantifiers(query: &Query, index: usize, arity: Arity) -> Result<(), String> {
+    let name = &query.capture_names()[index];
+    for pattern_index in 0..query.pattern_count() {
+        let quantifier = query.capture_quantifiers(pattern_index)[index];
+        let several = matches!(
+            quantifier,
+            CaptureQuantifier::ZeroOrMore | CaptureQuantifier::OneOrMore
+        );
+        let optional = matches!(
+            quantifier,
+            CaptureQuantifier::Zero | CaptureQuantifier::ZeroOrOne | CaptureQuantifier::ZeroOrMore
+        );
+
+        if several && arity != Arity::Many {
+            return Err(format!(
+                "the @{name} capture can match several nodes, use `Vec<Node<'tree>>` for it"
+            ));
+        }
+        if optional && arity == Arity::One {
+            return Err(format!(
+                "the @{name} capture can be missing from a match, use `Option<Node<'tree>>` for it"
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn error_at(tokens: &impl Spanned, message: &str) -> TokenStream {
+    quote_spanned!(tokens.span() => compile_error!(#message);)
+}
@@ -0,0 +1,99 @@
+//! Code generation for `symbol_table!`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use tree_sitter_c_proc_runtime::symbol_table;
+use tree_sitter_proc_core::symbols;
+
+use crate::grammar::Grammar;
+
+/// Generates a `SymbolTable` with perfect-hash maps of the grammar's node kinds
+/// and fields and arrays of its symbols and field names.
+pub(crate) fn expand(grammar: &Grammar) -> TokenStream {
+    let language = grammar.language();
+
+    let node_kinds = symbols::node_kinds(&language);
+    let keys: Vec<(&str, bool)> = node_kinds
+        .iter()
+        .map(|node_kind| (node_kind.name, node_kind.named))
+        .collect();
+    let layout = symbol_table::layout(&keys);
+    let kind_entries = layout.order.iter().map(|&index| {
+        let node_kind = &node_kinds[index];
+        let name = node_kind.name;
+        let named = node_kind.named;
+        let id = node_kind.id;
+        let ids = &node_kind.ids;
+        quote! {
+            ::tree_sitter_c_proc_runtime::symbol_table::NodeKind {
+                name: #name,
+                named: #named,
+                id: #id,
+                ids: &[#(#ids),*],
+            }
+        }
+    });
+    let kinds = map(&layout, kind_entries);
+
+    let symbols = (0..language.node_kind_count() as u16).map(|id| {
+        let name = language.node_kind_for_id(id).unwrap_or("");
+        let named = language.node_kind_is_named(id);
+        let visible = language.node_kind_is_visible(id);
+        quote! {
+            ::tree_sitter_c_proc_runtime::symbol_table::Symbol {
+                name: #name,
+                named: #named,
+                visible: #visible,
+            }
+        }
+    });
+
+    let field_names: Vec<&str> = symbols::field_ids(&language)
+        .map(|id| language.field_name_for_id(id).unwrap_or(""))
+        .collect();
+    let keys: Vec<(&str, bool)> = field_names.iter().map(|&name| (name, true)).collect();
+    let layout = symbol_table::layout(&keys);
+    let field_entries = layout.order.iter().map(|&index| {
+        let name = field_names[index];
+        let id = index as u16 + 1;
+        quote! {
+            ::tree_sitter_c_proc_runtime::fingerprint::Field {
+                name: #name,
+                id: #id,
+            }
+        }
+    });
+    let fields = map(&layout, field_entries);
+
+    let dependencies = grammar.dependencies();
+
+    quote! {
+        {
+            #dependencies
+
+            ::tree_sitter_c_proc_runtime::SymbolTable {
+                kinds: #kinds,
+                symbols: &[#(#symbols),*],
+                fields: #fields,
+                field_names: &[#(#field_names),*],
+            }
+        }
+    }
+}
+
+/// Generates a `Map` with the given layout and entries, in slot order.
+fn map(layout: &symbol_table::Layout, entries: impl Iterator<Item = TokenStream>) -> TokenStream {
+    let seed = layout.seed;
+    let displacements = layout
+        .displacements
+        .iter()
+        .map(|&(d1, d2)| quote!((#d1, #d2)));
+
+    quote! {
+        ::tree_sitter_c_proc_runtime::symbol_table::Map {
+            seed: #seed,
+            displacements: &[#(#displacements),*],
+            entries: &[#(#entries),*],
+        }
+    }
+}
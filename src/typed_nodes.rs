@@ -0,0 +1,401 @@
+//! Code generation for `typed_nodes!`.
+//!
+//! Every named node kind in `node-types.json` becomes a newtype around
+//! `tree_sitter::Node`, every supertype becomes an enum of its subtypes, and
+//! every field becomes an accessor typed by the kinds the field can hold.
+
+use std::collections::BTreeMap;
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use tree_sitter::Language;
+use tree_sitter_proc_core::naming;
+use tree_sitter_proc_core::node_types::{FieldInfo, NodeTypeRef, NodeTypes};
+use tree_sitter_proc_core::{symbols, LangName};
+
+/// Generates the typed node wrappers for the whole grammar.
+///
+/// A `node-types.json` that doesn't belong to the grammar, such as a custom
+/// one, can list kinds and fields the grammar lacks, which are reported at
+/// `span`.
+pub(crate) fn expand(
+    language: &Language,
+    lang_name: LangName,
+    node_types: &NodeTypes,
+    span: Span,
+) -> syn::Result<TokenStream> {
+    let mut generator = Generator::new(language, lang_name, node_types, span);
+    let mut items = Vec::new();
+
+    for node_type in node_types.node_types() {
+        if !node_type.named {
+            continue;
+        }
+        if node_type.subtypes.is_empty() {
+            items.push(generator.node_struct(&node_type.kind, &node_type.fields)?);
+        } else {
+            let ident = generator.type_ident(&node_type.kind)?;
+            let doc = format!("Any `{}` node.", node_type.kind);
+            items.push(generator.choice_enum(&ident, &doc, &node_type.subtypes)?);
+        }
+    }
+
+    // Enums for fields that can hold several kinds are collected while the
+    // structs are generated.
+    items.append(&mut generator.field_enums);
+
+    Ok(quote! {
+        #(#items)*
+    })
+}
+
+/// Shared state while generating the wrappers.
+struct Generator<'a> {
+    language: &'a Language,
+    lang_name: LangName<'a>,
+    /// Where errors are reported.
+    span: Span,
+    node_types: &'a NodeTypes,
+    /// The type generated for every named kind and supertype.
+    type_idents: BTreeMap<&'a str, Ident>,
+    /// Every type name in use, including field enums.
+    taken: Vec<String>,
+    field_enums: Vec<TokenStream>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(
+        language: &'a Language,
+        lang_name: LangName<'a>,
+        node_types: &'a NodeTypes,
+        span: Span,
+    ) -> Self {
+        let mut generator = Generator {
+            language,
+            lang_name,
+            span,
+            node_types,
+            type_idents: BTreeMap::new(),
+            taken: Vec::new(),
+            field_enums: Vec::new(),
+        };
+
+        // Concrete kinds are named first so they always keep their plain
+        // spelling; supertypes and field enums yield on collisions.
+        let named = node_types
+            .node_types()
+            .iter()
+            .filter(|node_type| node_type.named);
+        for node_type in named
+            .clone()
+            .filter(|node_type| node_type.subtypes.is_empty())
+        {
+            let ident = generator.unique_ident(&naming::upper_camel_case(&node_type.kind));
+            generator.type_idents.insert(&node_type.kind, ident);
+        }
+        for node_type in named.filter(|node_type| !node_type.subtypes.is_empty()) {
+            let ident = generator.unique_ident(&naming::upper_camel_case(&node_type.kind));
+            generator.type_idents.insert(&node_type.kind, ident);
+        }
+
+        generator
+    }
+
+    fn unique_ident(&mut self, base: &str) -> Ident {
+        let mut name = base.to_string();
+        let mut suffix = 2;
+        while self.taken.contains(&name) {
+            name = format!("{base}{suffix}");
+            suffix += 1;
+        }
+        self.taken.push(name.clone());
+        Ident::new(&name, Span::call_site())
+    }
+
+    /// Returns the type generated for the named kind `kind`.
+    fn type_ident(&self, kind: &str) -> syn::Result<Ident> {
+        match self.type_idents.get(kind) {
+            Some(ident) => Ok(ident.clone()),
+            None => Err(syn::Error::new(
+                self.span,
+                format!(
+                    "node-types.json refers to \"{kind}\" nodes, but doesn't list \"{kind}\" \
+                     as a node type"
+                ),
+            )),
+        }
+    }
+
+    /// Returns every symbol ID of the named kind `kind`.
+    fn ids(&self, kind: &str) -> syn::Result<Vec<u16>> {
+        let ids = symbols::ids_for_node_kind(self.language, kind, true);
+        if ids.is_empty() {
+            return Err(syn::Error::new(
+                self.span,
+                format!(
+                    "node-types.json lists \"{kind}\" nodes, but \"{kind}\" is not a node kind \
+                     in the {} grammar",
+                    self.lang_name.crate_name()
+                ),
+            ));
+        }
+        Ok(ids)
+    }
+
+    /// Generates the newtype for a concrete named kind.
+    fn node_struct(
+        &mut self,
+        kind: &str,
+        fields: &BTreeMap<String, FieldInfo>,
+    ) -> syn::Result<TokenStream> {
+        let ident = self.type_ident(kind)?;
+        let doc = format!("A `{kind}` node.");
+        let ids = self.ids(kind)?;
+        let accessors = fields
+            .iter()
+            .map(|(field_name, field_info)| self.accessor(kind, &ident, field_name, field_info))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        Ok(quote! {
+            #[doc = #doc]
+            #[derive(
+                ::core::clone::Clone,
+                ::core::marker::Copy,
+                ::core::fmt::Debug,
+                ::core::cmp::PartialEq,
+                ::core::cmp::Eq,
+                ::core::hash::Hash
+            )]
+            pub struct #ident<'tree>(::tree_sitter::Node<'tree>);
+
+            impl<'tree> #ident<'tree> {
+                /// Returns the underlying node.
+                pub fn node(&self) -> ::tree_sitter::Node<'tree> {
+                    self.0
+                }
+
+                #(#accessors)*
+            }
+
+            impl<'tree> ::core::convert::TryFrom<::tree_sitter::Node<'tree>> for #ident<'tree> {
+                type Error = ::tree_sitter::Node<'tree>;
+
+                fn try_from(
+                    node: ::tree_sitter::Node<'tree>,
+                ) -> ::core::result::Result<Self, ::tree_sitter::Node<'tree>> {
+                    match node.kind_id() {
+                        #(#ids)|* => ::core::result::Result::Ok(#ident(node)),
+                        _ => ::core::result::Result::Err(node),
+                    }
+                }
+            }
+
+            impl<'tree> ::core::convert::From<#ident<'tree>> for ::tree_sitter::Node<'tree> {
+                fn from(node: #ident<'tree>) -> Self {
+                    node.0
+                }
+            }
+        })
+    }
+
+    /// Generates an enum with one variant per kind in `choices`, used for
+    /// supertypes and for fields that can hold several kinds.
+    fn choice_enum(
+        &self,
+        ident: &Ident,
+        doc: &str,
+        choices: &[NodeTypeRef],
+    ) -> syn::Result<TokenStream> {
+        let mut variants = Vec::new();
+        let mut concrete_arms = Vec::new();
+        let mut nested_checks = Vec::new();
+
+        for choice in choices.iter().filter(|choice| choice.named) {
+            let variant = self.type_ident(&choice.kind)?;
+
+            if self.node_types.is_supertype(&choice.kind) {
+                nested_checks.push(quote! {
+                    if let ::core::result::Result::Ok(inner) =
+                        <#variant<'tree> as ::core::convert::TryFrom<_>>::try_from(node)
+                    {
+                        return ::core::result::Result::Ok(#ident::#variant(inner));
+                    }
+                });
+            } else {
+                let ids = self.ids(&choice.kind)?;
+                concrete_arms.push(quote! {
+                    #(#ids)|* => return ::core::result::Result::Ok(#ident::#variant(#variant(node))),
+                });
+            }
+
+            variants.push(variant);
+        }
+
+        Ok(quote! {
+            #[doc = #doc]
+            #[derive(
+                ::core::clone::Clone,
+                ::core::marker::Copy,
+                ::core::fmt::Debug,
+                ::core::cmp::PartialEq,
+                ::core::cmp::Eq,
+                ::core::hash::Hash
+            )]
+            pub enum #ident<'tree> {
+                #(#variants(#variants<'tree>),)*
+            }
+
+            impl<'tree> #ident<'tree> {
+                /// Returns the underlying node.
+                pub fn node(&self) -> ::tree_sitter::Node<'tree> {
+                    match self {
+                        #(#ident::#variants(inner) => inner.node(),)*
+                    }
+                }
+            }
+
+            impl<'tree> ::core::convert::TryFrom<::tree_sitter::Node<'tree>> for #ident<'tree> {
+                type Error = ::tree_sitter::Node<'tree>;
+
+                fn try_from(
+                    node: ::tree_sitter::Node<'tree>,
+                ) -> ::core::result::Result<Self, ::tree_sitter::Node<'tree>> {
+                    match node.kind_id() {
+                        #(#concrete_arms)*
+                        _ => {}
+                    }
+                    #(#nested_checks)*
+                    ::core::result::Result::Err(node)
+                }
+            }
+
+            impl<'tree> ::core::convert::From<#ident<'tree>> for ::tree_sitter::Node<'tree> {
+                fn from(node: #ident<'tree>) -> Self {
+                    node.node()
+                }
+            }
+        })
+    }
+
+    /// Returns the type a field holds, generating an enum if it can hold
+    /// several named kinds. Fields that can hold anonymous tokens are left as
+    /// plain nodes.
+    fn field_type(
+        &mut self,
+        owner: &Ident,
+        field_name: &str,
+        field_info: &FieldInfo,
+    ) -> syn::Result<TokenStream> {
+        Ok(match field_info.types.as_slice() {
+            _ if field_info.types.iter().any(|choice| !choice.named) => {
+                quote!(::tree_sitter::Node<'tree>)
+            }
+            [single] => {
+                let ident = self.type_ident(&single.kind)?;
+                quote!(#ident<'tree>)
+            }
+            choices => {
+                let base = format!("{owner}{}", naming::upper_camel_case(field_name));
+                let ident = self.unique_ident(&base);
+                let doc = format!("Any node the `{field_name}` field of [`{owner}`] can hold.");
+                let field_enum = self.choice_enum(&ident, &doc, choices)?;
+                self.field_enums.push(field_enum);
+                quote!(#ident<'tree>)
+            }
+        })
+    }
+
+    /// Generates the accessor for one field of a node kind.
+    fn accessor(
+        &mut self,
+        kind: &str,
+        owner: &Ident,
+        field_name: &str,
+        field_info: &FieldInfo,
+    ) -> syn::Result<TokenStream> {
+        let method = naming::snake_case_ident(field_name);
+        let Some(field_id) = self.language.field_id_for_name(field_name) else {
+            return Err(syn::Error::new(
+                self.span,
+                format!(
+                    "node-types.json lists a \"{field_name}\" field of \"{kind}\" nodes, but \
+                     \"{field_name}\" is not a field in the {} grammar",
+                    self.lang_name.crate_name()
+                ),
+            ));
+        };
+        let field_type = self.field_type(owner, field_name, field_info)?;
+        let is_node = field_info.types.iter().any(|choice| !choice.named);
+
+        Ok(if field_info.multiple {
+            let doc = format!("Returns the nodes in the `{field_name}` field.");
+            let yield_child = if is_node {
+                quote!(return ::core::option::Option::Some(node);)
+            } else {
+                quote! {
+                    if let ::core::result::Result::Ok(child) =
+                        ::core::convert::TryFrom::try_from(node)
+                    {
+                        return ::core::option::Option::Some(child);
+                    }
+                }
+            };
+
+            quote! {
+                #[doc = #doc]
+                pub fn #method(&self) -> impl ::core::iter::Iterator<Item = #field_type> + 'tree {
+                    let mut cursor = self.0.walk();
+                    let mut done = !cursor.goto_first_child();
+                    ::core::iter::from_fn(move || {
+                        while !done {
+                            let node = cursor.node();
+                            let field_id = cursor.field_id();
+                            done = !cursor.goto_next_sibling();
+                            if field_id == ::core::option::Option::Some(#field_id) {
+                                #yield_child
+                            }
+                        }
+                        ::core::option::Option::None
+                    })
+                }
+            }
+        } else if field_info.required {
+            let doc = format!(
+                "Returns the node in the `{field_name}` field.\n\n\
+                 # Panics\n\n\
+                 Panics if syntax errors left the field empty or holding an unexpected kind."
+            );
+            let missing = format!("`{kind}` node without a `{field_name}` field");
+            let unexpected = format!("unexpected kind in the `{field_name}` field of `{kind}`");
+            let convert = if is_node {
+                quote!(node)
+            } else {
+                quote!(::core::convert::TryFrom::try_from(node).expect(#unexpected))
+            };
+
+            quote! {
+                #[doc = #doc]
+                pub fn #method(&self) -> #field_type {
+                    let node = self.0.child_by_field_id(#field_id).expect(#missing);
+                    #convert
+                }
+            }
+        } else {
+            let doc = format!("Returns the node in the `{field_name}` field, if any.");
+            let convert = if is_node {
+                quote!(::core::option::Option::Some(node))
+            } else {
+                quote!(::core::convert::TryFrom::try_from(node).ok())
+            };
+
+            quote! {
+                #[doc = #doc]
+                pub fn #method(&self) -> ::core::option::Option<#field_type> {
+                    self.0
+                        .child_by_field_id(#field_id)
+                        .and_then(|node| #convert)
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,125 @@
+//! Code generation for `visitor!`.
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use tree_sitter_proc_core::naming;
+use tree_sitter_proc_core::symbols;
+
+use crate::grammar::Grammar;
+
+/// Generates the `CVisitor` trait, or its equivalent for another grammar, the
+/// `VisitControl` enum and the `walk` driver.
+pub(crate) fn expand(grammar: &Grammar) -> TokenStream {
+    let node_kinds = symbols::node_kinds(&grammar.language());
+    let visitor = format_ident!("{}Visitor", grammar.type_prefix);
+    let doc = format!(
+        "A visitor over a {} syntax tree, driven by `walk`.",
+        grammar.crate_name
+    );
+    let idents = naming::node_kind_idents(&node_kinds);
+
+    let mut methods = Vec::new();
+    let mut arms = Vec::new();
+    for (node_kind, ident) in node_kinds.iter().zip(&idents) {
+        if !node_kind.named {
+            continue;
+        }
+        let method = Ident::new(
+            &format!("visit_{}", naming::snake_case(ident)),
+            Span::call_site(),
+        );
+        let doc = format!("Called when `walk` reaches a `{}` node.", node_kind.name);
+        let ids = &node_kind.ids;
+
+        methods.push(quote! {
+            #[doc = #doc]
+            fn #method(&mut self, _node: ::tree_sitter::Node<'tree>) -> VisitControl {
+                VisitControl::Continue
+            }
+        });
+        arms.push(quote! {
+            #(#ids)|* => visitor.#method(node),
+        });
+    }
+
+    quote! {
+        /// Tells `walk` whether to descend into the children of a node.
+        #[derive(
+            ::core::clone::Clone,
+            ::core::marker::Copy,
+            ::core::fmt::Debug,
+            ::core::cmp::PartialEq,
+            ::core::cmp::Eq,
+            ::core::hash::Hash
+        )]
+        pub enum VisitControl {
+            /// Visit the children of the node.
+            Continue,
+            /// Skip the children of the node and move on to its next sibling.
+            SkipChildren,
+        }
+
+        #[doc = #doc]
+        ///
+        /// Every named node kind has a `visit_<kind>` method that does nothing
+        /// by default. `enter` and `leave` are called for every node, named or
+        /// anonymous, before and after it is visited.
+        pub trait #visitor<'tree> {
+            /// Called before a node is visited. Returning
+            /// `VisitControl::SkipChildren` skips both the `visit_<kind>`
+            /// method and the children of the node.
+            fn enter(&mut self, _node: ::tree_sitter::Node<'tree>) -> VisitControl {
+                VisitControl::Continue
+            }
+
+            /// Called after a node and its children have been visited, even if
+            /// they were skipped.
+            fn leave(&mut self, _node: ::tree_sitter::Node<'tree>) {}
+
+            #(#methods)*
+        }
+
+        /// Walks `tree` depth-first, calling `visitor` for every node.
+        ///
+        /// The walk uses a single `TreeCursor`, so it doesn't recurse and is
+        /// safe on arbitrarily deep trees. Nodes are dispatched to their
+        /// `visit_<kind>` method by matching on the node kind ID, which compiles
+        /// to a jump table.
+        pub fn walk<'tree, V>(tree: &'tree ::tree_sitter::Tree, visitor: &mut V)
+        where
+            V: #visitor<'tree> + ?::core::marker::Sized,
+        {
+            fn dispatch<'tree, V>(visitor: &mut V, node: ::tree_sitter::Node<'tree>) -> VisitControl
+            where
+                V: #visitor<'tree> + ?::core::marker::Sized,
+            {
+                match node.kind_id() {
+                    #(#arms)*
+                    _ => VisitControl::Continue,
+                }
+            }
+
+            let mut cursor = tree.walk();
+            loop {
+                let node = cursor.node();
+                let control = match visitor.enter(node) {
+                    VisitControl::Continue => dispatch(visitor, node),
+                    VisitControl::SkipChildren => VisitControl::SkipChildren,
+                };
+                if control == VisitControl::Continue && cursor.goto_first_child() {
+                    continue;
+                }
+                visitor.leave(node);
+
+                // Climb until there is a next sibling, leaving every finished
+                // ancestor on the way.
+                while !cursor.goto_next_sibling() {
+                    if !cursor.goto_parent() {
+                        return;
+                    }
+                    visitor.leave(cursor.node());
+                }
+            }
+        }
+    }
+}
@@ -1,71 +1,69 @@
-use proc_macro::TokenStream;
-
-use quote::{quote, quote_spanned};
-use syn::{parse_macro_input, LitStr};
+//! Proc macros for compile-time access to tree-sitter-LANG node kinds,
+//! keywords, and fields.
+//!
+//! The lookups and error messages are shared with the macros of every other
+//! grammar in `tree_sitter_proc_core`.
 
-#[proc_macro]
-pub fn kind(token_stream : TokenStream) -> TokenStream {
-    let string_literal : LitStr = parse_macro_input!(token_stream);
+use proc_macro::TokenStream;
 
-    // Get the string value and calculate its length
-    let requested_kind = string_literal.value();
+use tree_sitter_proc_core::LangName;
 
-    let language = tree_sitter_LANG::language();
-    let found_id = language.id_for_node_kind(&requested_kind, true);
+const GRAMMAR: LangName =
+    LangName::new("tree-sitter-LANG").with_node_types(tree_sitter_LANG::NODE_TYPES);
 
-    if found_id != 0 {
-        quote! {
-            #found_id
-        }
-    } else {
-        quote_spanned!(
-            string_literal.span() =>
-            compile_error!("This is not a valid node kind in the tree-sitter-LANG grammar")
-        )
-    }.into()
+/// Returns the node kind ID for a given node kind name.
+#[proc_macro]
+pub fn kind(token_stream: TokenStream) -> TokenStream {
+    tree_sitter_proc_core::expand_kind(&tree_sitter_LANG::language(), token_stream.into(), GRAMMAR)
+        .into()
 }
 
+/// Returns an or-pattern matching any of several node kinds.
 #[proc_macro]
-pub fn kw(token_stream : TokenStream) -> TokenStream {
-    let string_literal : LitStr = parse_macro_input!(token_stream);
-
-    // Get the string value and calculate its length
-    let requested_keyword = string_literal.value();
-
-    let language = tree_sitter_LANG::language();
-    let found_id = language.id_for_node_kind(&requested_keyword, false);
+pub fn kinds(token_stream: TokenStream) -> TokenStream {
+    tree_sitter_proc_core::expand_kinds(&tree_sitter_LANG::language(), token_stream.into(), GRAMMAR)
+        .into()
+}
 
-    if found_id != 0 {
-        quote! {
-            #found_id
-        }
-    } else {
-        quote_spanned!(
-            string_literal.span() =>
-            compile_error!("This is not a valid keyword in the tree-sitter-LANG grammar")
-        )
-    }.into()
+/// Returns the node kind ID for a given keyword.
+#[proc_macro]
+pub fn kw(token_stream: TokenStream) -> TokenStream {
+    tree_sitter_proc_core::expand_kw(&tree_sitter_LANG::language(), token_stream.into(), GRAMMAR)
+        .into()
 }
 
+/// Returns an or-pattern matching any of several keywords.
 #[proc_macro]
-pub fn field(token_stream : TokenStream) -> TokenStream {
-    let string_literal : LitStr = parse_macro_input!(token_stream);
+pub fn kws(token_stream: TokenStream) -> TokenStream {
+    tree_sitter_proc_core::expand_kws(&tree_sitter_LANG::language(), token_stream.into(), GRAMMAR)
+        .into()
+}
 
-    // Get the string value and calculate its length
-    let requested_keyword = string_literal.value();
+/// Returns an or-pattern matching every concrete subtype of a supertype.
+#[proc_macro]
+pub fn supertype(token_stream: TokenStream) -> TokenStream {
+    tree_sitter_proc_core::expand_supertype(
+        &tree_sitter_LANG::language(),
+        token_stream.into(),
+        GRAMMAR,
+    )
+    .into()
+}
 
-    let language = tree_sitter_LANG::language();
-    let found_id = language.field_id_for_name(&requested_keyword);
+/// Returns the field ID for a given field name as a `NonZeroU16`.
+#[proc_macro]
+pub fn field(token_stream: TokenStream) -> TokenStream {
+    tree_sitter_proc_core::expand_field(&tree_sitter_LANG::language(), token_stream.into(), GRAMMAR)
+        .into()
+}
 
-    if let Some(found_id) = found_id {
-        let id_number : u16 = found_id.into();
-        quote! {
-            std::num::NonZeroU16::new(#id_number).unwrap()
-        }
-    } else {
-        quote_spanned!(
-            string_literal.span() =>
-            compile_error!("This is not a valid field in the tree-sitter-LANG grammar")
-        )
-    }.into()
-}
\ No newline at end of file
+/// Returns the field ID for a given field name as a plain `u16`.
+#[proc_macro]
+pub fn field_id(token_stream: TokenStream) -> TokenStream {
+    tree_sitter_proc_core::expand_field_id(
+        &tree_sitter_LANG::language(),
+        token_stream.into(),
+        GRAMMAR,
+    )
+    .into()
+}
@@ -0,0 +1,93 @@
+// Crates that use the macros from outside, for the tests of what the macros
+// read from the calling crate and its environment
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// A crate that depends on tree-sitter-c-proc by path.
+pub struct Fixture {
+    pub directory: PathBuf,
+}
+
+impl Fixture {
+    /// Creates the crate `name` with `metadata` appended to its manifest and
+    /// `lib` as its `src/lib.rs`.
+    pub fn new(name: &str, metadata: &str, lib: &str) -> Fixture {
+        let directory = Path::new(env!("CARGO_TARGET_TMPDIR"))
+            .join("fixtures")
+            .join(name);
+        let _ = fs::remove_dir_all(&directory);
+        let fixture = Fixture { directory };
+        fixture.write_manifest(metadata);
+        fixture.write("src/lib.rs", lib);
+        fixture
+    }
+
+    /// Replaces the metadata of the manifest.
+    pub fn write_manifest(&self, metadata: &str) {
+        let name = self.directory.file_name().unwrap().to_string_lossy();
+        self.write(
+            "Cargo.toml",
+            &format!(
+                "[package]\n\
+                 name = \"fixture-{name}\"\n\
+                 version = \"0.0.0\"\n\
+                 edition = \"2021\"\n\
+                 publish = false\n\
+                 \n\
+                 [dependencies]\n\
+                 tree-sitter = \"0.20.10\"\n\
+                 tree-sitter-c-proc = {{ path = {:?} }}\n\
+                 \n\
+                 {metadata}\n\
+                 \n\
+                 # Not a member of the workspace the fixture is in\n\
+                 [workspace]\n",
+                env!("CARGO_MANIFEST_DIR")
+            ),
+        );
+    }
+
+    /// Writes `contents` to `path`, relative to the crate.
+    pub fn write(&self, path: &str, contents: &str) {
+        let path = self.directory.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    /// Returns a `cargo check` of the crate, which shares a target directory
+    /// with the other fixtures so that the macros are only built once.
+    ///
+    /// Everything the fixtures need was fetched to build this crate, so cargo
+    /// runs offline.
+    pub fn check(&self) -> Command {
+        let mut command = Command::new(env!("CARGO"));
+        command
+            .args(["check", "--offline", "--quiet", "--manifest-path"])
+            .arg(self.directory.join("Cargo.toml"))
+            .env(
+                "CARGO_TARGET_DIR",
+                Path::new(env!("CARGO_TARGET_TMPDIR")).join("fixtures-target"),
+            );
+        command
+    }
+}
+
+/// Asserts that a `cargo check` succeeded.
+pub fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Asserts that a `cargo check` failed with `message`.
+pub fn assert_error(output: &Output, message: &str) {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success(), "expected an error");
+    assert!(
+        stderr.contains(message),
+        "expected `{message}` in:\n{stderr}"
+    );
+}
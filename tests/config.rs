@@ -0,0 +1,163 @@
+// Tests for the [package.metadata.tree-sitter-proc] table of the calling crate
+mod common;
+
+use common::{assert_error, assert_success, Fixture};
+use serde_json::{json, Value};
+
+/// Returns the node-types.json of tree-sitter-c with `change` applied.
+fn node_types(change: impl FnOnce(&mut Vec<Value>)) -> String {
+    let mut node_types: Vec<Value> = serde_json::from_str(tree_sitter_c::NODE_TYPES).unwrap();
+    change(&mut node_types);
+    serde_json::to_string(&node_types).unwrap()
+}
+
+fn node_type<'a>(node_types: &'a mut [Value], kind: &str) -> &'a mut Value {
+    node_types
+        .iter_mut()
+        .find(|node_type| node_type["type"] == kind && node_type["named"] == true)
+        .unwrap()
+}
+
+#[test]
+fn test_language() {
+    let lib = r#"
+        tree_sitter_c_proc::track_grammar!();
+        const _: u16 = tree_sitter_c_proc::kind!("if_statement");
+    "#;
+    let fixture = Fixture::new(
+        "language",
+        "[package.metadata.tree-sitter-proc]\nlanguage = \"c\"",
+        lib,
+    );
+    assert_success(&fixture.check().output().unwrap());
+
+    // Changes to the manifest are picked up
+    fixture.write_manifest("[package.metadata.tree-sitter-proc]\nlanguage = \"cobol\"");
+    assert_error(
+        &fixture.check().output().unwrap(),
+        "`cobol` is not a supported grammar; the supported grammars are `c`, `cpp`, `rust`",
+    );
+
+    fixture.write_manifest("[package.metadata.tree-sitter-proc]\nlanguage = \"cpp\"");
+    assert_error(
+        &fixture.check().output().unwrap(),
+        "the `cpp` grammar is not enabled; enable the `lang-cpp` feature of tree-sitter-c-proc",
+    );
+
+    // Without the table, the macros use C
+    fixture.write_manifest("");
+    assert_success(&fixture.check().output().unwrap());
+}
+
+#[test]
+fn test_invalid_table() {
+    let fixture = Fixture::new(
+        "invalid-table",
+        "[package.metadata.tree-sitter-proc]\nlanguage = \"c\"\ngrammar = \"c\"",
+        r#"const _: u16 = tree_sitter_c_proc::kind!("if_statement");"#,
+    );
+    assert_error(
+        &fixture.check().output().unwrap(),
+        "invalid [package.metadata.tree-sitter-proc] in",
+    );
+    assert_error(
+        &fixture.check().output().unwrap(),
+        "unknown field `grammar`",
+    );
+}
+
+#[test]
+fn test_node_types() {
+    // Identifiers are no expressions in the custom node-types.json
+    let custom = node_types(|node_types| {
+        let subtypes = node_type(node_types, "_expression")["subtypes"]
+            .as_array_mut()
+            .unwrap();
+        subtypes.retain(|subtype| subtype["type"] != "identifier");
+    });
+    let metadata = "[package.metadata.tree-sitter-proc]\nnode-types = \"grammar/node-types.json\"";
+    let fixture = Fixture::new(
+        "node-types",
+        metadata,
+        r#"
+        use tree_sitter_c_proc::{kind, supertype};
+
+        tree_sitter_c_proc::track_grammar!();
+
+        const _: () = assert!(!matches!(kind!("identifier"), supertype!("_expression")));
+        const _: () = assert!(matches!(kind!("number_literal"), supertype!("_expression")));
+        "#,
+    );
+    fixture.write("grammar/node-types.json", &custom);
+    assert_success(&fixture.check().output().unwrap());
+
+    // Changes to the file are picked up
+    fixture.write("grammar/node-types.json", tree_sitter_c::NODE_TYPES);
+    assert_error(
+        &fixture.check().output().unwrap(),
+        "assertion failed: !matches!(kind!(\"identifier\"), supertype!(\"_expression\"))",
+    );
+
+    fixture.write("grammar/node-types.json", "[{\"type\": 1}]");
+    assert_error(&fixture.check().output().unwrap(), "is not valid");
+
+    fixture.write_manifest(
+        "[package.metadata.tree-sitter-proc]\nnode-types = \"grammar/missing.json\"",
+    );
+    assert_error(&fixture.check().output().unwrap(), "failed to read");
+}
+
+#[test]
+fn test_node_types_not_of_the_grammar() {
+    // `typed_nodes!` reports kinds and fields the grammar doesn't have
+    let metadata = "[package.metadata.tree-sitter-proc]\nnode-types = \"node-types.json\"";
+    let fixture = Fixture::new(
+        "foreign-node-types",
+        metadata,
+        "tree_sitter_c_proc::typed_nodes!();",
+    );
+
+    fixture.write(
+        "node-types.json",
+        &node_types(|node_types| {
+            node_type(node_types, "if_statement")["fields"]["otherwise"] = json!({
+                "multiple": false,
+                "required": false,
+                "types": [{"type": "compound_statement", "named": true}],
+            });
+        }),
+    );
+    assert_error(
+        &fixture.check().output().unwrap(),
+        "node-types.json lists a \"otherwise\" field of \"if_statement\" nodes, but \"otherwise\" \
+         is not a field in the tree-sitter-c grammar",
+    );
+
+    fixture.write(
+        "node-types.json",
+        &node_types(|node_types| {
+            node_types.push(json!({"type": "unless_statement", "named": true, "fields": {}}));
+        }),
+    );
+    assert_error(
+        &fixture.check().output().unwrap(),
+        "node-types.json lists \"unless_statement\" nodes, but \"unless_statement\" is not a node \
+         kind in the tree-sitter-c grammar",
+    );
+
+    // Kinds that fields and supertypes refer to need their own entry
+    fixture.write(
+        "node-types.json",
+        &node_types(|node_types| {
+            node_types.retain(|node_type| node_type["type"] != "compound_statement");
+        }),
+    );
+    assert_error(
+        &fixture.check().output().unwrap(),
+        "node-types.json refers to \"compound_statement\" nodes, but doesn't list \
+         \"compound_statement\" as a node type",
+    );
+
+    fixture.write("node-types.json", tree_sitter_c::NODE_TYPES);
+    assert_success(&fixture.check().output().unwrap());
+}
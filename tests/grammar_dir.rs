@@ -0,0 +1,174 @@
+// Tests for grammars read from TREE_SITTER_C_GRAMMAR_DIR
+mod common;
+
+use std::env::consts::DLL_EXTENSION;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+use common::{assert_error, assert_success, Fixture};
+use serde_json::Value;
+
+const VARIABLE: &str = "TREE_SITTER_C_GRAMMAR_DIR";
+
+const LIB: &str = r#"
+    use tree_sitter_c_proc::{kind, supertype};
+
+    tree_sitter_c_proc::track_grammar!();
+
+    pub const IS_EXPRESSION: bool = matches!(kind!("identifier"), supertype!("_expression"));
+"#;
+
+/// Returns the directory of the tree-sitter-c crate this crate depends on.
+fn tree_sitter_c_crate() -> PathBuf {
+    let output = Command::new(env!("CARGO"))
+        .args(["metadata", "--offline", "--format-version", "1"])
+        .arg("--manifest-path")
+        .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml"))
+        .output()
+        .unwrap();
+    let metadata: Value = serde_json::from_slice(&output.stdout).unwrap();
+    let package = metadata["packages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|package| package["name"] == "tree-sitter-c")
+        .unwrap();
+    let manifest = Path::new(package["manifest_path"].as_str().unwrap());
+    manifest.parent().unwrap().to_path_buf()
+}
+
+/// Compiles `sources` into a shared library at `library` with the system C
+/// compiler.
+fn compile(sources: &[PathBuf], include: &Path, library: &Path) {
+    let status = Command::new("cc")
+        .args(["-shared", "-fPIC", "-O0", "-I"])
+        .arg(include)
+        .args(sources)
+        .arg("-o")
+        .arg(library)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+/// Returns the parser of tree-sitter-c compiled into a shared library, which
+/// is kept between runs.
+fn parser() -> &'static Path {
+    static PARSER: OnceLock<PathBuf> = OnceLock::new();
+    PARSER.get_or_init(|| {
+        let library = Path::new(env!("CARGO_TARGET_TMPDIR"))
+            .join(format!("tree-sitter-c-parser.{DLL_EXTENSION}"));
+        if !library.exists() {
+            let source = tree_sitter_c_crate().join("src");
+            compile(&[source.join("parser.c")], &source, &library);
+        }
+        library
+    })
+}
+
+/// Creates a grammar directory with the files of tree-sitter-c, leaving out
+/// the compiled parser.
+fn grammar_dir(name: &str) -> PathBuf {
+    let directory = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("grammars")
+        .join(name);
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(directory.join("src")).unwrap();
+    let source = tree_sitter_c_crate().join("src");
+    for file in ["grammar.json", "node-types.json"] {
+        fs::copy(source.join(file), directory.join("src").join(file)).unwrap();
+    }
+    directory
+}
+
+#[test]
+fn test_missing_dir() {
+    let fixture = Fixture::new("missing-grammar-dir", "", LIB);
+    let missing = Path::new(env!("CARGO_TARGET_TMPDIR")).join("no-such-grammar");
+    let output = fixture.check().env(VARIABLE, &missing).output().unwrap();
+    assert_error(
+        &output,
+        "failed to load the grammar in TREE_SITTER_C_GRAMMAR_DIR: failed to read",
+    );
+    assert_error(&output, &missing.display().to_string());
+}
+
+#[test]
+fn test_missing_parser() {
+    let directory = grammar_dir("missing-parser");
+    let fixture = Fixture::new("missing-parser", "", LIB);
+    let output = fixture.check().env(VARIABLE, &directory).output().unwrap();
+    assert_error(&output, "found no compiled parser in");
+
+    // The other files are required as well
+    fs::remove_file(directory.join("src/node-types.json")).unwrap();
+    let output = fixture.check().env(VARIABLE, &directory).output().unwrap();
+    assert_error(&output, "node-types.json");
+}
+
+#[test]
+#[cfg(unix)]
+fn test_abi_mismatch() {
+    // Only the ABI version, the first field of a language, is read before the
+    // check
+    let directory = grammar_dir("abi-mismatch");
+    fs::write(
+        directory.join("src/language.c"),
+        "static const unsigned int language[64] = {1};\n\
+         const void *tree_sitter_c(void) { return language; }\n",
+    )
+    .unwrap();
+    compile(
+        &[directory.join("src/language.c")],
+        &directory.join("src"),
+        &directory.join(format!("parser.{DLL_EXTENSION}")),
+    );
+
+    let fixture = Fixture::new("abi-mismatch", "", LIB);
+    let output = fixture.check().env(VARIABLE, &directory).output().unwrap();
+    assert_error(
+        &output,
+        "was generated for version 1 of the tree-sitter language ABI",
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn test_grammar_dir() {
+    let directory = grammar_dir("grammar-dir");
+    fs::copy(parser(), directory.join(format!("parser.{DLL_EXTENSION}"))).unwrap();
+    let fixture = Fixture::new("grammar-dir", "", LIB);
+    fixture.write(
+        "src/lib.rs",
+        &format!("{LIB}\nconst _: () = assert!(IS_EXPRESSION);\n"),
+    );
+    assert_success(&fixture.check().env(VARIABLE, &directory).output().unwrap());
+
+    // Identifiers are no expressions in the node-types.json of the directory,
+    // which makes cargo check the crate again
+    let node_types_path = directory.join("src/node-types.json");
+    let mut node_types: Vec<Value> =
+        serde_json::from_str(&fs::read_to_string(&node_types_path).unwrap()).unwrap();
+    let expression = node_types
+        .iter_mut()
+        .find(|node_type| node_type["type"] == "_expression")
+        .unwrap();
+    expression["subtypes"]
+        .as_array_mut()
+        .unwrap()
+        .retain(|subtype| subtype["type"] != "identifier");
+    fs::write(
+        &node_types_path,
+        serde_json::to_string(&node_types).unwrap(),
+    )
+    .unwrap();
+    assert_error(
+        &fixture.check().env(VARIABLE, &directory).output().unwrap(),
+        "assertion failed: IS_EXPRESSION",
+    );
+
+    // Without the variable, the crate's grammar is used again
+    assert_success(&fixture.check().output().unwrap());
+}
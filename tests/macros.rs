@@ -1,5 +1,17 @@
 // Tests for the tree-sitter-c-proc macros
-use tree_sitter_c_proc::{field, kind, kw};
+use std::num::NonZeroU16;
+
+use tree_sitter_c_proc::{
+    field, field_id, field_path, grammar_fingerprint, kind, kind_set, kinds, kw, kws, match_node,
+    node_kind_enum, query, supertype, symbol_table, typed_nodes, visitor, CQuery,
+};
+
+node_kind_enum!();
+visitor!();
+
+mod ast {
+    super::typed_nodes!();
+}
 
 #[test]
 fn test_kind_macro() {
@@ -41,6 +53,179 @@ fn test_kind_macro() {
     // kind!("not_a_valid_node_kind") // This would fail at compile time
 }
 
+#[test]
+fn test_kinds_macro_with_several_ids() {
+    // function_definition belongs to several symbol IDs in the grammar
+    let language = tree_sitter_c::language();
+    let function_def_ids: Vec<u16> = (0..language.node_kind_count() as u16)
+        .filter(|&id| {
+            language.node_kind_is_named(id)
+                && language.node_kind_for_id(id) == Some("function_definition")
+        })
+        .collect();
+    assert!(
+        function_def_ids.len() > 1,
+        "Expected function_definition to have several symbol IDs"
+    );
+
+    // Verify that the or-pattern matches every one of them
+    for function_def_id in function_def_ids {
+        assert!(
+            matches!(function_def_id, kinds!("function_definition")),
+            "Expected symbol {} to match kinds!(\"function_definition\")",
+            function_def_id
+        );
+    }
+
+    // Verify that the or-pattern matches the ID reported by a parsed node
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser
+        .parse("int main(void) { return (0); }", None)
+        .unwrap();
+    let function_def = tree.root_node().child(0).unwrap();
+    assert!(
+        matches!(function_def.kind_id(), kinds!("function_definition")),
+        "Expected the parsed node to match kinds!(\"function_definition\")"
+    );
+    assert!(
+        !matches!(tree.root_node().kind_id(), kinds!("function_definition")),
+        "Expected translation_unit not to match kinds!(\"function_definition\")"
+    );
+
+    // `(` is a keyword with several symbol IDs, too
+    let open_paren_ids = language.node_kind_count() as u16;
+    let open_paren_ids: Vec<u16> = (0..open_paren_ids)
+        .filter(|&id| matches!(id, kws!("(")))
+        .collect();
+    assert!(open_paren_ids.len() > 1);
+}
+
+#[test]
+fn test_kind_macro_with_several_ids() {
+    // Nodes of an aliased kind report a single one of its IDs, which is the
+    // value of kind!, even where the node is parsed through an alias
+    let code = "int main(void) { return (f)(1) + (x); }";
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(tree_sitter_c::language()).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+
+    let mut ids = Vec::new();
+    for node in tree_sitter_c_proc_runtime::traversal::descendants(tree.root_node()) {
+        let expanded = match node.kind() {
+            "function_definition" => kind!("function_definition"),
+            "call_expression" => kind!("call_expression"),
+            "binary_expression" => kind!("binary_expression"),
+            "parenthesized_expression" => kind!("parenthesized_expression"),
+            "(" => kw!("("),
+            _ => continue,
+        };
+        ids.push((node.kind_id(), expanded));
+    }
+
+    assert!(ids.len() >= 8, "{ids:?}");
+    for (reported, expanded) in ids {
+        assert_eq!(reported, expanded);
+    }
+}
+
+#[test]
+fn test_error_kind() {
+    // Unparseable input is reported as `ERROR`, which isn't in the symbol
+    // table but has a fixed ID
+    assert_eq!(kind!("ERROR"), u16::MAX);
+    assert!(matches!(u16::MAX, kinds!("ERROR")));
+    assert!(!matches!(kind!("identifier"), kinds!("ERROR")));
+
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(tree_sitter_c::language()).unwrap();
+    let tree = parser.parse("int main(void) { ) }", None).unwrap();
+    let error = tree_sitter_c_proc_runtime::traversal::descendants(tree.root_node())
+        .find(|node| node.is_error())
+        .unwrap();
+    assert_eq!(error.kind_id(), kind!("ERROR"));
+    assert!(matches!(error.kind_id(), kinds!("ERROR", "identifier")));
+}
+
+#[test]
+fn test_kinds_macro() {
+    // Test that every listed node kind matches the or-pattern
+    let call_expression_id = kind!("call_expression");
+    let cast_expression_id = kind!("cast_expression");
+    let identifier_id = kind!("identifier");
+
+    assert!(
+        matches!(
+            call_expression_id,
+            kinds!("call_expression", "cast_expression")
+        ),
+        "Expected call_expression to match the or-pattern"
+    );
+    assert!(
+        matches!(
+            cast_expression_id,
+            kinds!("call_expression", "cast_expression")
+        ),
+        "Expected cast_expression to match the or-pattern"
+    );
+    assert!(
+        !matches!(identifier_id, kinds!("call_expression", "cast_expression")),
+        "Expected identifier not to match the or-pattern"
+    );
+
+    // Test the or-pattern in a match arm, combined with other patterns
+    let description = match identifier_id {
+        kinds!("call_expression", "cast_expression") => "expression",
+        kinds!("identifier") | kw!("if") => "identifier or if",
+        _ => "other",
+    };
+    assert_eq!(description, "identifier or if");
+
+    // Note: Each invalid name produces its own compile error, so we can't test that here
+    // Example of what would cause a compile error:
+    // kinds!("call_expression", "not_a_valid_node_kind") // This would fail at compile time
+}
+
+#[test]
+fn test_supertype_macro() {
+    // Test that concrete subtypes match their supertype
+    let call_expression_id = kind!("call_expression");
+    let if_statement_id = kind!("if_statement");
+    let pointer_declarator_id = kind!("pointer_declarator");
+    let struct_specifier_id = kind!("struct_specifier");
+
+    assert!(
+        matches!(call_expression_id, supertype!("_expression")),
+        "Expected call_expression to be an _expression"
+    );
+    assert!(
+        matches!(if_statement_id, supertype!("_statement")),
+        "Expected if_statement to be a _statement"
+    );
+    assert!(
+        matches!(pointer_declarator_id, supertype!("_declarator")),
+        "Expected pointer_declarator to be a _declarator"
+    );
+    assert!(
+        matches!(struct_specifier_id, supertype!("_type_specifier")),
+        "Expected struct_specifier to be a _type_specifier"
+    );
+
+    // Verify that kinds outside the supertype don't match
+    assert!(
+        !matches!(if_statement_id, supertype!("_expression")),
+        "Expected if_statement not to be an _expression"
+    );
+    assert!(
+        !matches!(call_expression_id, supertype!("_statement")),
+        "Expected call_expression not to be a _statement"
+    );
+
+    // Note: Testing with invalid supertypes would cause compile errors, so we can't test that here
+    // Example of what would cause a compile error:
+    // supertype!("function_definition") // This would fail at compile time
+}
+
 #[test]
 fn test_kw_macro() {
     // Test with valid keywords
@@ -106,27 +291,727 @@ fn test_field_macro() {
     // field!("not_a_valid_field") // This would fail at compile time
 }
 
+#[test]
+fn test_field_macro_with_kind() {
+    // Test that a field checked against its node kind has the same ID
+    assert_eq!(
+        field!("function_definition", "declarator"),
+        field!("declarator")
+    );
+    assert_eq!(field!("if_statement", "condition"), field!("condition"));
+    assert_eq!(
+        field_id!("call_expression", "arguments"),
+        field!("arguments").get()
+    );
+
+    // Note: Fields that the node kind can't have would cause compile errors, so we can't test that here
+    // Example of what would cause a compile error:
+    // field!("function_definition", "name") // This would fail at compile time
+}
+
+#[test]
+fn test_field_id_macro() {
+    // Test that field_id! is the plain u16 behind field!
+    assert_eq!(field_id!("declarator"), field!("declarator").get());
+    assert_eq!(field_id!("body"), field!("body").get());
+
+    // Test field_id! in match patterns
+    let body_id = Some(field!("body").get());
+    let description = match body_id {
+        Some(field_id!("declarator")) => "declarator",
+        Some(field_id!("body")) => "body",
+        _ => "other",
+    };
+    assert_eq!(description, "body");
+
+    // Test field! in const items
+    const BODY_FIELD_ID: NonZeroU16 = field!("body");
+    assert_eq!(BODY_FIELD_ID, field!("body"));
+}
+
+// The expansions only refer to `::core`, so a local `std` must not break them
+mod shadowed_std {
+    #[allow(dead_code)]
+    mod std {}
+
+    use tree_sitter_c_proc::{field, field_id};
+
+    #[test]
+    fn test_field_macros_with_shadowed_std() {
+        const TYPE_FIELD_ID: ::core::num::NonZeroU16 = field!("type");
+        assert_eq!(TYPE_FIELD_ID.get(), field_id!("type"));
+    }
+}
+
+#[test]
+fn test_node_kind_enum_macro() {
+    // Test that variants carry the IDs computed by kind! and kw!
+    assert_eq!(CKind::IfStatement.id(), kind!("if_statement"));
+    assert_eq!(CKind::If.id(), kw!("if"));
+    assert_eq!(
+        CKind::try_from(kind!("if_statement")),
+        Ok(CKind::IfStatement)
+    );
+    assert_eq!(CKind::try_from(kw!("if")), Ok(CKind::If));
+
+    // Test names, named-ness and Display
+    assert_eq!(CKind::IfStatement.as_str(), "if_statement");
+    assert!(CKind::IfStatement.is_named());
+    assert_eq!(CKind::PlusEq.as_str(), "+=");
+    assert!(!CKind::PlusEq.is_named());
+    assert_eq!(CKind::LParen.to_string(), "(");
+    assert_eq!(CKind::Error.id(), u16::MAX);
+
+    // Verify that every symbol ID of an aliased kind converts to the same variant
+    let language = tree_sitter_c::language();
+    for id in 0..language.node_kind_count() as u16 {
+        if language.node_kind_for_id(id) == Some("function_definition") {
+            assert_eq!(CKind::try_from(id), Ok(CKind::FunctionDefinition));
+        }
+    }
+
+    // Verify that unknown IDs are returned as the error
+    assert_eq!(CKind::try_from(u16::MAX - 1), Err(u16::MAX - 1));
+
+    // Test matching on the kind of a parsed node
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser.parse("int main(void) { return 0; }", None).unwrap();
+    let function_def = tree.root_node().child(0).unwrap();
+    let description = match CKind::try_from(function_def.kind_id()) {
+        Ok(CKind::FunctionDefinition) => "function",
+        Ok(CKind::Declaration) => "declaration",
+        Ok(_) => "other",
+        Err(_) => "unknown",
+    };
+    assert_eq!(description, "function");
+}
+
+#[test]
+fn test_typed_nodes_macro() {
+    let code = "int x = 1, *y; int main(void) { if (x) return x; }";
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(tree_sitter_c::language()).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+    let root = tree.root_node();
+
+    // Test casting through TryFrom
+    let declaration = ast::Declaration::try_from(root.child(0).unwrap()).unwrap();
+    assert!(
+        ast::FunctionDefinition::try_from(root.child(0).unwrap()).is_err(),
+        "Expected a declaration not to cast to a function definition"
+    );
+    assert_eq!(declaration.node(), root.child(0).unwrap());
+
+    // Test a required field typed by a supertype
+    let type_specifier = declaration.r#type();
+    assert!(matches!(
+        type_specifier,
+        ast::TypeSpecifier::PrimitiveType(_)
+    ));
+    assert_eq!(
+        type_specifier.node().utf8_text(code.as_bytes()).unwrap(),
+        "int"
+    );
+
+    // Test a field holding several nodes of several kinds
+    let declarators: Vec<_> = declaration.declarator().collect();
+    assert_eq!(declarators.len(), 2);
+    assert!(matches!(
+        declarators[0],
+        ast::DeclarationDeclarator::InitDeclarator(_)
+    ));
+    assert!(matches!(
+        declarators[1],
+        ast::DeclarationDeclarator::PointerDeclarator(_)
+    ));
+
+    // Test nested supertypes
+    let function = ast::FunctionDefinition::try_from(root.child(1).unwrap()).unwrap();
+    let ast::Declarator::FunctionDeclarator(function_declarator) = function.declarator() else {
+        panic!("Expected a function declarator");
+    };
+    let ast::FunctionDeclaratorDeclarator::Declarator(ast::Declarator::Identifier(name)) =
+        function_declarator.declarator()
+    else {
+        panic!("Expected the function name to be an identifier");
+    };
+    assert_eq!(name.node().utf8_text(code.as_bytes()).unwrap(), "main");
+
+    // Test a required field with a single kind
+    let ast::DeclarationDeclarator::InitDeclarator(init_declarator) = declarators[0] else {
+        panic!("Expected an init declarator");
+    };
+    let value = init_declarator.value();
+    assert_eq!(value.node().utf8_text(code.as_bytes()).unwrap(), "1");
+
+    // Test an optional field
+    let if_statement = function
+        .body()
+        .node()
+        .named_child(0)
+        .and_then(|node| ast::IfStatement::try_from(node).ok())
+        .unwrap();
+    assert!(if_statement.alternative().is_none());
+}
+
+#[test]
+fn test_visitor_macro() {
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+        identifiers: Vec<std::ops::Range<usize>>,
+        depth: usize,
+        max_depth: usize,
+    }
+
+    impl<'tree> CVisitor<'tree> for Recorder {
+        fn enter(&mut self, node: tree_sitter::Node<'tree>) -> VisitControl {
+            self.depth += 1;
+            self.max_depth = self.max_depth.max(self.depth);
+            if node.kind_id() == kind!("compound_statement") {
+                // Skip function bodies
+                VisitControl::SkipChildren
+            } else {
+                VisitControl::Continue
+            }
+        }
+
+        fn leave(&mut self, node: tree_sitter::Node<'tree>) {
+            self.depth -= 1;
+            if node.is_named() {
+                self.events.push(format!("leave {}", node.kind()));
+            }
+        }
+
+        fn visit_function_definition(&mut self, _node: tree_sitter::Node<'tree>) -> VisitControl {
+            self.events.push("function_definition".to_string());
+            VisitControl::Continue
+        }
+
+        fn visit_identifier(&mut self, node: tree_sitter::Node<'tree>) -> VisitControl {
+            self.identifiers.push(node.byte_range());
+            VisitControl::Continue
+        }
+
+        fn visit_parameter_list(&mut self, _node: tree_sitter::Node<'tree>) -> VisitControl {
+            VisitControl::SkipChildren
+        }
+    }
+
+    let code = "int main(int argc) { return argc; }";
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(tree_sitter_c::language()).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+
+    let mut recorder = Recorder::default();
+    walk(&tree, &mut recorder);
+
+    // Every entered node is left again
+    assert_eq!(recorder.depth, 0);
+    assert!(recorder.max_depth >= 4);
+    // Only the function name is visited, the parameter and the body are skipped
+    let identifiers: Vec<&str> = recorder
+        .identifiers
+        .iter()
+        .map(|range| &code[range.clone()])
+        .collect();
+    assert_eq!(identifiers, vec!["main"]);
+    assert_eq!(
+        recorder.events,
+        vec![
+            "function_definition",
+            "leave primitive_type",
+            "leave identifier",
+            "leave parameter_list",
+            "leave function_declarator",
+            "leave compound_statement",
+            "leave function_definition",
+            "leave translation_unit",
+        ]
+    );
+}
+
+#[test]
+fn test_query_macro() {
+    let query = query!(
+        r#"
+        (call_expression
+          function: (identifier) @fn
+          arguments: (argument_list (number_literal) @function.argument))
+        "#
+    );
+
+    // Test the capture indices
+    assert_eq!(query.r#fn, 0);
+    assert_eq!(query.function_argument, 1);
+    assert_eq!(query.capture_names(), ["fn", "function.argument"]);
+
+    // Test that the query is only compiled once
+    let again: &tree_sitter::Query = query;
+    assert!(std::ptr::eq(again, &**query));
+
+    let code = "int main(void) { f(1); g(x); return h(2, 3); }";
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(tree_sitter_c::language()).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+
+    let mut cursor = tree_sitter::QueryCursor::new();
+    let mut calls = Vec::new();
+    for query_match in cursor.matches(query, tree.root_node(), code.as_bytes()) {
+        let text = |index| {
+            let capture = query_match
+                .captures
+                .iter()
+                .find(|capture| capture.index == index)
+                .unwrap();
+            capture.node.utf8_text(code.as_bytes()).unwrap()
+        };
+        calls.push(format!(
+            "{}({})",
+            text(query.r#fn),
+            text(query.function_argument)
+        ));
+    }
+    assert_eq!(calls, ["f(1)", "h(2)", "h(3)"]);
+}
+
+query!(
+    struct Calls,
+    r#"
+    (call_expression
+      function: (identifier) @fn
+      arguments: (argument_list (number_literal) @function.argument))
+    "#
+);
+
+// The capture indices are constants
+const FUNCTION_ARGUMENT: u32 = Calls::FUNCTION_ARGUMENT;
+
+#[test]
+fn test_query_macro_struct() {
+    assert_eq!(Calls::FN, 0);
+    assert_eq!(FUNCTION_ARGUMENT, 1);
+    assert_eq!(Calls::query().capture_names(), ["fn", "function.argument"]);
+    assert!(std::ptr::eq(Calls::query(), Calls::query()));
+
+    let code = "int main(void) { f(1); g(x); return h(2, 3); }";
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(tree_sitter_c::language()).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+
+    // Test matching on the capture indices
+    let mut cursor = tree_sitter::QueryCursor::new();
+    let mut captures = Vec::new();
+    for query_match in cursor.matches(Calls::query(), tree.root_node(), code.as_bytes()) {
+        for capture in query_match.captures {
+            let text = capture.node.utf8_text(code.as_bytes()).unwrap();
+            captures.push(match capture.index {
+                Calls::FN => format!("function {text}"),
+                Calls::FUNCTION_ARGUMENT => format!("argument {text}"),
+                _ => unreachable!(),
+            });
+        }
+    }
+    assert_eq!(
+        captures,
+        [
+            "function f",
+            "argument 1",
+            "function h",
+            "argument 2",
+            "function h",
+            "argument 3"
+        ]
+    );
+}
+
+#[derive(CQuery)]
+#[query(
+    r#"
+    (declaration
+      type: (_) @type
+      declarator: [
+        (identifier) @name
+        (init_declarator declarator: (identifier) @name value: (_) @value)
+      ])
+    (translation_unit (function_definition)+ @functions)
+    "#
+)]
+struct Declaration<'tree> {
+    r#type: Option<tree_sitter::Node<'tree>>,
+    name: Option<tree_sitter::Node<'tree>>,
+    value: Option<tree_sitter::Node<'tree>>,
+    functions: Vec<tree_sitter::Node<'tree>>,
+}
+
+#[test]
+fn test_derive_c_query() {
+    let code = "int x; long y = 2; void f(void) {} void g(void) {}";
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(tree_sitter_c::language()).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+    let text = |node: tree_sitter::Node| node.utf8_text(code.as_bytes()).unwrap().to_string();
+
+    // Test that the query is only compiled once
+    assert!(std::ptr::eq(Declaration::query(), Declaration::query()));
+    assert_eq!(Declaration::query().pattern_count(), 2);
+
+    let mut cursor = tree_sitter::QueryCursor::new();
+    let matches: Vec<Declaration> =
+        Declaration::matches(&mut cursor, tree.root_node(), code.as_bytes()).collect();
+    assert_eq!(matches.len(), 3);
+
+    // Test optional captures
+    assert_eq!(matches[0].r#type.map(text).as_deref(), Some("int"));
+    assert_eq!(matches[0].name.map(text).as_deref(), Some("x"));
+    assert_eq!(matches[0].value, None);
+    assert!(matches[0].functions.is_empty());
+    assert_eq!(matches[1].name.map(text).as_deref(), Some("y"));
+    assert_eq!(matches[1].value.map(text).as_deref(), Some("2"));
+
+    // Test quantified captures
+    assert_eq!(matches[2].name, None);
+    let functions: Vec<String> = matches[2].functions.iter().copied().map(text).collect();
+    assert_eq!(functions, ["void f(void) {}", "void g(void) {}"]);
+
+    // Test building the struct from a match of another iterator
+    let mut cursor = tree_sitter::QueryCursor::new();
+    let query_match = cursor
+        .matches(Declaration::query(), tree.root_node(), code.as_bytes())
+        .next()
+        .unwrap();
+    assert_eq!(Declaration::from_match(&query_match).name, matches[0].name);
+}
+
+#[test]
+fn test_match_node_macro() {
+    let code = "void f(void) { g(1); x == 2; x != 3; y = -x; if (x) return; }";
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(tree_sitter_c::language()).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+    let text = |node: tree_sitter::Node| node.utf8_text(code.as_bytes()).unwrap().to_string();
+
+    let classify = |node: tree_sitter::Node| -> String {
+        match_node!(node, {
+            // Test captures in nested patterns
+            (call_expression
+                function: (identifier) @function
+                arguments: (argument_list) @arguments) => {
+                format!("call {}{}", text(function), text(arguments))
+            }
+            // Test anonymous tokens
+            (binary_expression operator: "==" right: _ @right) => format!("equals {}", text(right)),
+            // Test supertypes and capturing the matched node itself
+            (assignment_expression right: (_expression) @value) @assignment => {
+                format!("{} assigns {}", text(assignment), text(value))
+            }
+            // Test absent fields
+            (if_statement !alternative condition: (_) @condition) => {
+                format!("if without else {}", text(condition))
+            }
+            (_ operator: _) => "operator".to_string(),
+            _ => "other".to_string(),
+        })
+    };
+
+    let body = tree.root_node().child(0).unwrap().child(2).unwrap();
+    let statements: Vec<String> = (0..body.named_child_count())
+        .map(|index| {
+            let statement = body.named_child(index).unwrap();
+            match statement.named_child(0) {
+                Some(child) if matches!(statement.kind_id(), kinds!("expression_statement")) => {
+                    classify(child)
+                }
+                _ => classify(statement),
+            }
+        })
+        .collect();
+
+    assert_eq!(
+        statements,
+        [
+            "call g(1)",
+            "equals 2",
+            "operator",
+            "y = -x assigns -x",
+            "if without else (x)",
+        ]
+    );
+    assert_eq!(classify(body), "other");
+}
+
+#[test]
+fn test_field_path_macro() {
+    let code = "int a, *b = 0; int (*handler)(int signal);";
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(tree_sitter_c::language()).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+    let text = |node: tree_sitter::Node| node.utf8_text(code.as_bytes()).unwrap().to_string();
+    let first = tree.root_node().child(0).unwrap();
+    let second = tree.root_node().child(1).unwrap();
+
+    // Test plain fields, keywords as field names and the grammar argument
+    assert_eq!(
+        field_path!(first => declarator).map(text),
+        Some("a".to_string())
+    );
+    assert_eq!(
+        field_path!(first => type).map(text),
+        Some("int".to_string())
+    );
+    assert_eq!(
+        field_path!(c, first => r#type).map(text),
+        Some("int".to_string())
+    );
+
+    // Test filters, which search every child in the field
+    let initialized = field_path!(first => [declaration] declarator[init_declarator] / declarator);
+    assert_eq!(initialized.map(text), Some("*b".to_string()));
+    let value = field_path!(first => declarator[init_declarator] / value[_expression]);
+    assert_eq!(value.map(text), Some("0".to_string()));
+
+    // Test `*` and supertype filters through nested declarators
+    let parameter = field_path!(
+        second => declarator[function_declarator]
+            / declarator / *[_declarator] / declarator
+    );
+    assert_eq!(parameter.map(text), Some("handler".to_string()));
+    let signal = field_path!(
+        second => declarator / parameters / *[parameter_declaration] / declarator
+    );
+    assert_eq!(signal.map(text), Some("signal".to_string()));
+
+    // Test short-circuiting when a step finds nothing
+    assert_eq!(
+        field_path!(first => declarator[function_declarator] / parameters),
+        None
+    );
+    assert_eq!(field_path!(second => [translation_unit] *), None);
+    assert_eq!(field_path!(first => declarator / declarator), None);
+
+    // Test that `*` skips extras such as comments
+    let code = "void f(/* none */ int x); int g(/* one */ int y, int z);";
+    let tree = parser.parse(code, None).unwrap();
+    let text = |node: tree_sitter::Node| node.utf8_text(code.as_bytes()).unwrap().to_string();
+    let first = tree.root_node().child(0).unwrap();
+    let second = tree.root_node().child(1).unwrap();
+    assert_eq!(
+        field_path!(first => declarator / parameters / *).map(text),
+        Some("int x".to_string())
+    );
+    assert_eq!(
+        field_path!(second => declarator / parameters / *[parameter_declaration] / declarator)
+            .map(text),
+        Some("y".to_string())
+    );
+}
+
+#[test]
+fn test_grammar_argument() {
+    // Naming the default grammar gives the same IDs as leaving it out
+    assert_eq!(kind!(c, "if_statement"), kind!("if_statement"));
+    assert_eq!(kw!(c, "if"), kw!("if"));
+    assert_eq!(field!(c, "declarator"), field!("declarator"));
+    assert_eq!(
+        field!(c, "function_definition", "body"),
+        field!("function_definition", "body")
+    );
+    assert!(matches!(
+        kind!("translation_unit"),
+        kind!(c, "translation_unit")
+    ));
+    assert!(matches!(
+        kind!("cast_expression"),
+        kinds!(c, "call_expression", "cast_expression")
+    ));
+    assert!(matches!(
+        kind!("cast_expression"),
+        supertype!(c, "_expression")
+    ));
+    assert!(matches!(
+        Some(field!("declarator").get()),
+        Some(field_id!(c, "declarator"))
+    ));
+
+    let calls = query!(c, "(call_expression function: (identifier) @function)");
+    assert_eq!(calls.function, 0);
+}
+
+#[test]
+fn test_grammar_fingerprint_macro() {
+    const C_GRAMMAR: tree_sitter_c_proc_runtime::Fingerprint = grammar_fingerprint!();
+
+    // The grammar the tests link is the one the macros read
+    assert_eq!(
+        C_GRAMMAR.hash,
+        tree_sitter_c_proc_runtime::fingerprint::hash(&tree_sitter_c::language())
+    );
+    assert_eq!(
+        C_GRAMMAR.verify_language(&tree_sitter_c::language()),
+        Ok(())
+    );
+
+    // It records the IDs the macros expand to
+    let function_definition = C_GRAMMAR
+        .kinds
+        .iter()
+        .find(|kind| kind.name == "function_definition" && kind.named)
+        .unwrap();
+    assert!(function_definition.ids.len() > 1);
+    assert!(function_definition
+        .ids
+        .iter()
+        .all(|&id| matches!(id, kinds!("function_definition"))));
+    let declarator = C_GRAMMAR
+        .fields
+        .iter()
+        .find(|field| field.name == "declarator")
+        .unwrap();
+    assert_eq!(declarator.id, field!("declarator").get());
+}
+
+#[test]
+fn test_kind_set_macro() {
+    use tree_sitter_c_proc_runtime::KindSet;
+
+    const LOOPS: KindSet<6> = kind_set!("for_statement", "while_statement", "do_statement");
+    const STATEMENTS: KindSet<6> = kind_set!("_statement");
+    const CALLS: KindSet<6> = kind_set!(c, "call_expression", "ERROR");
+
+    assert!(LOOPS.contains(kind!("for_statement")));
+    assert!(!LOOPS.contains(kind!("if_statement")));
+    assert_eq!(LOOPS.len(), 3);
+
+    // Supertypes stand for their subtypes, and sets combine in constants
+    const OTHER_STATEMENTS: KindSet<6> = STATEMENTS.difference(LOOPS);
+    assert_eq!(STATEMENTS.intersection(LOOPS), LOOPS);
+    assert!(OTHER_STATEMENTS.contains(kind!("return_statement")));
+    assert!(!OTHER_STATEMENTS.contains(kind!("while_statement")));
+    for id in STATEMENTS.iter() {
+        assert!(matches!(id, supertype!("_statement")));
+    }
+
+    // Aliased names have all of their IDs, and ERROR has its own
+    let language = tree_sitter_c::language();
+    let call_expression_ids: Vec<u16> = (0..language.node_kind_count() as u16)
+        .filter(|&id| matches!(id, kinds!("call_expression")))
+        .chain([u16::MAX])
+        .collect();
+    assert!(call_expression_ids.len() > 2);
+    assert_eq!(CALLS.iter().collect::<Vec<_>>(), call_expression_ids);
+
+    // Nodes of a tree can be filtered by the set
+    let code = "int main() { for (;;) {} while (1) {} if (1) {} }";
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(language).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+    let body = tree.root_node().child(0).unwrap().child(2).unwrap();
+    let mut cursor = body.walk();
+    let loops = body
+        .named_children(&mut cursor)
+        .filter(|child| LOOPS.contains(child.kind_id()))
+        .count();
+    assert_eq!(loops, 2);
+}
+
+#[test]
+fn test_symbol_table_macro() {
+    static C_SYMBOLS: tree_sitter_c_proc_runtime::SymbolTable = symbol_table!();
+    let language = tree_sitter_c::language();
+
+    // Every lookup agrees with the grammar's own tables
+    assert_eq!(C_SYMBOLS.node_kind_count(), language.node_kind_count());
+    for id in 0..language.node_kind_count() as u16 {
+        let name = language.node_kind_for_id(id).unwrap();
+        let named = language.node_kind_is_named(id);
+        assert_eq!(C_SYMBOLS.node_kind_for_id(id), Some(name));
+        assert_eq!(C_SYMBOLS.node_kind_is_named(id), named);
+        assert_eq!(
+            C_SYMBOLS.node_kind_is_visible(id),
+            language.node_kind_is_visible(id)
+        );
+        let found = C_SYMBOLS.id_for_node_kind(name, named).unwrap_or(0);
+        assert_eq!(found, language.id_for_node_kind(name, named), "{name:?}");
+    }
+    assert_eq!(C_SYMBOLS.field_count(), language.field_count());
+    for id in 1..=language.field_count() as u16 {
+        let name = language.field_name_for_id(id).unwrap();
+        assert_eq!(C_SYMBOLS.field_name_for_id(id), Some(name));
+        assert_eq!(C_SYMBOLS.field_id_for_name(name), Some(id));
+    }
+
+    // Aliased names have all of their IDs, and ERROR has its own
+    let function_definition = C_SYMBOLS.ids_for_node_kind("function_definition", true);
+    assert!(function_definition.len() > 1);
+    assert!(function_definition
+        .iter()
+        .all(|&id| matches!(id, kinds!("function_definition"))));
+    assert_eq!(C_SYMBOLS.id_for_node_kind("ERROR", true), Some(u16::MAX));
+    assert_eq!(C_SYMBOLS.node_kind_for_id(u16::MAX), Some("ERROR"));
+
+    // Unknown names are None rather than 0
+    assert_eq!(C_SYMBOLS.id_for_node_kind("function_defintion", true), None);
+    assert_eq!(C_SYMBOLS.id_for_node_kind("if_statement", false), None);
+    assert!(C_SYMBOLS.ids_for_node_kind("", true).is_empty());
+    assert_eq!(C_SYMBOLS.field_id_for_name("captures"), None);
+    assert_eq!(C_SYMBOLS.field_name_for_id(0), None);
+    assert_eq!(
+        C_SYMBOLS.node_kind_for_id(language.node_kind_count() as u16),
+        None
+    );
+}
+
+#[cfg(feature = "lang-cpp")]
+#[test]
+fn test_cpp_grammar() {
+    tree_sitter_c_proc::node_kind_enum!(cpp);
+
+    // The enum and the macros agree on the IDs of the C++ grammar, which
+    // differ from those of the C grammar
+    let class_specifier = CppKind::try_from(kind!(cpp, "class_specifier")).unwrap();
+    assert_eq!(class_specifier, CppKind::ClassSpecifier);
+    assert_eq!(class_specifier.as_str(), "class_specifier");
+    assert_eq!(
+        CppKind::try_from(kw!(cpp, "namespace")).unwrap().as_str(),
+        "namespace"
+    );
+    assert_ne!(kind!(cpp, "if_statement"), kind!("if_statement"));
+}
+
 #[test]
 fn test_macro_integration() {
     // Test using the macros together in a realistic scenario
-    let function_def_id = kind!("function_definition");
     let declarator_id = field!("declarator").get();
     let name_id = field!("name").get();
 
     // Verify that the IDs are valid
-    assert!(
-        function_def_id > 0,
-        "Expected non-zero ID for function_definition"
-    );
     assert!(
         declarator_id > 0,
         "Expected non-zero ID for declarator field"
     );
     assert!(name_id > 0, "Expected non-zero ID for name field");
 
-    // In a real scenario, we would use these IDs to navigate a tree-sitter parse tree
-    // For example, to find the name of a function definition:
-    // node.child_by_field_id(declarator_id)
-    //     .and_then(|n| n.child_by_field_id(declarator_id))
-    //     .and_then(|n| n.child_by_field_id(name_id))
+    // Use the IDs to navigate a tree-sitter parse tree and find the name of a
+    // function definition
+    let code = "int main(void) { return 0; }";
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(tree_sitter_c::language()).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+
+    let node = tree.root_node().child(0).unwrap();
+    assert!(
+        matches!(node.kind_id(), kinds!("function_definition")),
+        "Expected a function_definition"
+    );
+
+    let name = node
+        .child_by_field_id(declarator_id)
+        .and_then(|n| n.child_by_field_id(declarator_id))
+        .unwrap();
+    assert!(
+        matches!(name.kind_id(), kind!("identifier")),
+        "Expected the function name to be an identifier"
+    );
+    assert_eq!(name.utf8_text(code.as_bytes()).unwrap(), "main");
 }
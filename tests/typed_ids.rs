@@ -0,0 +1,77 @@
+// Tests for the macros with the `typed` argument
+use tree_sitter_c_proc::{field, field_id, kind, kinds, kw, node_kind_enum, supertype};
+use tree_sitter_c_proc_runtime::{FieldId, KindId, NodeExt, TokenId, TreeCursorExt};
+
+node_kind_enum!();
+
+#[test]
+fn test_typed_ids() {
+    let language = tree_sitter_c::language();
+
+    // The macros expand to the typed IDs, which wrap the grammar's IDs
+    let if_statement: KindId = kind!(typed, "if_statement");
+    assert_eq!(
+        if_statement,
+        language.id_for_node_kind("if_statement", true)
+    );
+    assert_eq!(if_statement, kind!("if_statement"));
+    let if_keyword: TokenId = kw!(typed, "if");
+    assert_eq!(if_keyword.raw(), language.id_for_node_kind("if", false));
+    let body: FieldId = field!(typed, "body");
+    assert_eq!(body, language.field_id_for_name("body").unwrap());
+
+    // The grammar argument comes first
+    let translation_unit: KindId = kind!(c, typed, "translation_unit");
+    assert_eq!(translation_unit, kind!(c, "translation_unit"));
+
+    // The IDs are constant expressions
+    const CONDITION: FieldId = field!(typed, "if_statement", "condition");
+    const IF_STATEMENT: u16 = kind!(typed, "if_statement").raw();
+    assert_eq!(CONDITION.get(), field_id!("condition"));
+    assert_eq!(IF_STATEMENT, if_statement);
+
+    // The enum converts from the raw IDs
+    assert_eq!(CKind::try_from(if_statement.raw()), Ok(CKind::IfStatement));
+    assert_eq!(CKind::try_from(if_keyword.raw()), Ok(CKind::If));
+}
+
+#[test]
+fn test_typed_ids_in_patterns() {
+    let code = "int main(void) { if (x) { return 0; } }";
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(tree_sitter_c::language()).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+
+    let root = tree.root_node();
+    assert!(matches!(
+        root.kind_id_typed(),
+        kind!(typed, "translation_unit")
+    ));
+
+    let function = root.child(0).unwrap();
+    assert!(matches!(
+        function.kind_id_typed(),
+        kinds!(typed, "function_definition")
+    ));
+
+    let body = function
+        .child_by_field_id_typed(field!(typed, "body"))
+        .unwrap();
+    let if_statement = body.named_child(0).unwrap();
+    let label = match if_statement.kind_id_typed() {
+        kinds!(typed, "while_statement", "for_statement") => "loop",
+        kind!(typed, "if_statement") => "if",
+        supertype!(typed, "_expression") => "expression",
+        _ => "other",
+    };
+    assert_eq!(label, "if");
+
+    let if_keyword = if_statement.child(0).unwrap();
+    assert!(matches!(if_keyword.token_id_typed(), kw!(typed, "if")));
+
+    let mut cursor = if_statement.walk();
+    cursor.goto_first_child();
+    assert_eq!(cursor.field_id_typed(), None);
+    cursor.goto_next_sibling();
+    assert_eq!(cursor.field_id_typed(), Some(field!(typed, "condition")));
+}
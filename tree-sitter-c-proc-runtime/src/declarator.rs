@@ -0,0 +1,139 @@
+//! The name and type modifiers of tree-sitter-c declarators.
+//!
+//! C spells a declaration's type partly around its name, and tree-sitter-c
+//! nests a node for every part: the declarator of
+//! `Point* create_point(int x)` is a `pointer_declarator` wrapping a
+//! `function_declarator` wrapping the `identifier`. [`resolve`] walks down to
+//! the name and collects the pointer, array and function modifiers on the way.
+//!
+//! ```
+//! use tree_sitter_c_proc_runtime::declarator::{self, Modifier};
+//!
+//! let code = "Point* create_point(int x);";
+//! let mut parser = tree_sitter::Parser::new();
+//! parser.set_language(tree_sitter_c::language()).unwrap();
+//! let tree = parser.parse(code, None).unwrap();
+//! let declaration = tree.root_node().child(0).unwrap();
+//!
+//! let resolved = declarator::resolve(declaration.child_by_field_name("declarator").unwrap());
+//! let name = resolved.name.unwrap();
+//! assert_eq!(name.utf8_text(code.as_bytes()).unwrap(), "create_point");
+//! assert!(matches!(
+//!     resolved.modifiers.as_slice(),
+//!     [Modifier::Pointer(_), Modifier::Function(_)]
+//! ));
+//! assert!(resolved.is_function());
+//! ```
+//!
+//! Node kinds are matched by name rather than by ID, so the resolver works
+//! with whichever version of tree-sitter-c the calling crate links.
+
+use tree_sitter::Node;
+
+/// A type modifier a declarator applies, with its node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Modifier<'tree> {
+    /// A `pointer_declarator` or `abstract_pointer_declarator`, whose
+    /// `type_qualifier` children qualify the pointer.
+    Pointer(Node<'tree>),
+    /// An `array_declarator` or `abstract_array_declarator`, with the `size`
+    /// field.
+    Array(Node<'tree>),
+    /// A `function_declarator` or `abstract_function_declarator`, with the
+    /// `parameters` field.
+    Function(Node<'tree>),
+}
+
+impl<'tree> Modifier<'tree> {
+    /// Returns the declarator node that applies the modifier.
+    pub fn node(&self) -> Node<'tree> {
+        match *self {
+            Modifier::Pointer(node) | Modifier::Array(node) | Modifier::Function(node) => node,
+        }
+    }
+}
+
+/// The result of [`resolve`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Declarator<'tree> {
+    /// The declared `identifier`, `field_identifier`, `type_identifier` or
+    /// `primitive_type`, or `None` for an abstract declarator, such as the `*`
+    /// of `(int *)p`, or one that contains a syntax error.
+    pub name: Option<Node<'tree>>,
+    /// The modifiers the declarator applies, outermost first.
+    ///
+    /// The type reads from the name outwards, so from the end: the modifiers
+    /// of `*table[8]` are `[Pointer, Array]`, an array of pointers, and those
+    /// of `(*handler)(int)` are `[Function, Pointer]`, a pointer to a function.
+    pub modifiers: Vec<Modifier<'tree>>,
+}
+
+impl Declarator<'_> {
+    /// Returns `true` if the declarator declares a function rather than a
+    /// function pointer or a variable.
+    pub fn is_function(&self) -> bool {
+        matches!(self.modifiers.last(), Some(Modifier::Function(_)))
+    }
+
+    /// Returns `true` if the declarator declares a pointer, such as a
+    /// function pointer.
+    pub fn is_pointer(&self) -> bool {
+        matches!(self.modifiers.last(), Some(Modifier::Pointer(_)))
+    }
+}
+
+/// Walks down from `node`, the `declarator` field of a declaration, function
+/// definition, field or parameter declaration, type definition or type
+/// descriptor, to the name it declares.
+///
+/// Pointer, array, function, parenthesized, attributed and init declarators
+/// are walked through, and so are their abstract counterparts in casts,
+/// `sizeof` and parameter lists. A name on its own resolves to itself without
+/// modifiers. That includes a `primitive_type`, since tree-sitter-c parses
+/// names like `size_t` as one even where a typedef declares them. The walk is
+/// a loop, so however deeply declarators nest, it doesn't recurse.
+pub fn resolve(node: Node<'_>) -> Declarator<'_> {
+    let mut modifiers = Vec::new();
+    let mut next = Some(node);
+
+    while let Some(node) = next {
+        next = match node.kind() {
+            "identifier" | "field_identifier" | "type_identifier" | "primitive_type" => {
+                return Declarator {
+                    name: Some(node),
+                    modifiers,
+                };
+            }
+            "pointer_declarator" | "abstract_pointer_declarator" => {
+                modifiers.push(Modifier::Pointer(node));
+                node.child_by_field_name("declarator")
+            }
+            "array_declarator" | "abstract_array_declarator" => {
+                modifiers.push(Modifier::Array(node));
+                node.child_by_field_name("declarator")
+            }
+            "function_declarator" | "abstract_function_declarator" => {
+                modifiers.push(Modifier::Function(node));
+                node.child_by_field_name("declarator")
+            }
+            "init_declarator" => node.child_by_field_name("declarator"),
+            // The wrapped declarator isn't in a field, but it's the only
+            // named child besides attributes
+            "parenthesized_declarator"
+            | "abstract_parenthesized_declarator"
+            | "attributed_declarator" => {
+                let mut cursor = node.walk();
+                let inner = node
+                    .named_children(&mut cursor)
+                    .find(|child| child.kind() != "attribute_declaration");
+                inner
+            }
+            _ => None,
+        };
+    }
+
+    Declarator {
+        name: None,
+        modifiers,
+    }
+}
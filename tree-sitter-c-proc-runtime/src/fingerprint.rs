@@ -0,0 +1,224 @@
+//! Checking at runtime that a `Language` has the IDs the macros baked in.
+//!
+//! `kind!` and the other macros read IDs from the grammar crate the proc macro
+//! was built with. If the calling crate links a different version of that
+//! grammar, the IDs can silently mean other node kinds. `grammar_fingerprint!()`
+//! records the symbol and field tables the macros saw, and
+//! [`Fingerprint::verify_language`] compares them with the `Language` the
+//! program actually uses.
+
+use std::error::Error;
+use std::fmt;
+
+use tree_sitter::Language;
+
+use tree_sitter_proc_core::symbols::{field_ids, ids_for_node_kind};
+
+/// The symbol and field tables of a grammar, as `grammar_fingerprint!()`
+/// recorded them at compile time.
+#[derive(Clone, Copy, Debug)]
+pub struct Fingerprint {
+    /// The hash [`hash`] computes for the grammar.
+    pub hash: u64,
+    /// Every visible node kind.
+    pub kinds: &'static [Kind],
+    /// Every field.
+    pub fields: &'static [Field],
+}
+
+/// A visible node kind and every symbol ID that carries its name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Kind {
+    pub name: &'static str,
+    pub named: bool,
+    pub ids: &'static [u16],
+}
+
+/// A field and its ID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Field {
+    pub name: &'static str,
+    pub id: u16,
+}
+
+impl Fingerprint {
+    /// Checks that `language` has the same symbol and field tables as the
+    /// grammar the macros used.
+    ///
+    /// Call this once at startup with the `Language` the program parses with.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`Mismatch`] listing the node kinds and fields whose IDs
+    /// differ, if the tables aren't the same.
+    pub fn verify_language(&self, language: &Language) -> Result<(), Mismatch> {
+        let found = hash(language);
+        if found == self.hash {
+            return Ok(());
+        }
+
+        let moved_kinds = self
+            .kinds
+            .iter()
+            .filter_map(|kind| {
+                let found_ids = ids_for_node_kind(language, kind.name, kind.named);
+                (found_ids != kind.ids).then_some(MovedKind {
+                    name: kind.name,
+                    named: kind.named,
+                    expected: kind.ids,
+                    found: found_ids,
+                })
+            })
+            .collect();
+
+        let moved_fields = self
+            .fields
+            .iter()
+            .filter_map(|field| {
+                let found_id = language.field_id_for_name(field.name);
+                (found_id != Some(field.id)).then_some(MovedField {
+                    name: field.name,
+                    expected: field.id,
+                    found: found_id,
+                })
+            })
+            .collect();
+
+        Err(Mismatch {
+            expected: self.hash,
+            found,
+            moved_kinds,
+            moved_fields,
+        })
+    }
+}
+
+/// The differences between the grammar the macros used and the `Language`
+/// passed to [`Fingerprint::verify_language`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    /// The fingerprint the macros recorded.
+    pub expected: u64,
+    /// The fingerprint of the `Language` at runtime.
+    pub found: u64,
+    /// The node kinds whose symbol IDs differ, in the order of the recorded
+    /// table.
+    pub moved_kinds: Vec<MovedKind>,
+    /// The fields whose IDs differ.
+    pub moved_fields: Vec<MovedField>,
+}
+
+/// A node kind whose symbol IDs differ at runtime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MovedKind {
+    pub name: &'static str,
+    pub named: bool,
+    /// The IDs the macros expand to.
+    pub expected: &'static [u16],
+    /// The IDs at runtime, empty if the kind no longer exists.
+    pub found: Vec<u16>,
+}
+
+/// A field whose ID differs at runtime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MovedField {
+    pub name: &'static str,
+    /// The ID the macros expand to.
+    pub expected: u16,
+    /// The ID at runtime, `None` if the field no longer exists.
+    pub found: Option<u16>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the grammar differs from the one the macros were expanded with \
+             (fingerprint {:#018x}, expected {:#018x})",
+            self.found, self.expected
+        )?;
+
+        if self.moved_kinds.is_empty() && self.moved_fields.is_empty() {
+            return write!(f, "; no node kind or field moved, but symbols were added");
+        }
+        for kind in &self.moved_kinds {
+            let name = if kind.named {
+                kind.name.to_string()
+            } else {
+                format!("{:?}", kind.name)
+            };
+            if kind.found.is_empty() {
+                write!(f, "; node kind {name} {:?} is gone", kind.expected)?;
+            } else {
+                write!(
+                    f,
+                    "; node kind {name} moved from {:?} to {:?}",
+                    kind.expected, kind.found
+                )?;
+            }
+        }
+        for field in &self.moved_fields {
+            match field.found {
+                Some(found) => write!(
+                    f,
+                    "; field {} moved from {} to {found}",
+                    field.name, field.expected
+                )?,
+                None => write!(f, "; field {} ({}) is gone", field.name, field.expected)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Error for Mismatch {}
+
+/// Hashes the symbol and field tables of `language`.
+///
+/// Every symbol's ID, name, named-ness and visibility and every field's ID and
+/// name go into the hash, so any change that can move an ID changes it. This
+/// is the hash `grammar_fingerprint!()` records at compile time.
+pub fn hash(language: &Language) -> u64 {
+    let mut hasher = Fnv1a::new();
+
+    hasher.write_u16(language.node_kind_count() as u16);
+    for id in 0..language.node_kind_count() as u16 {
+        hasher.write_u16(id);
+        hasher.write(language.node_kind_for_id(id).unwrap_or("").as_bytes());
+        hasher.write(&[
+            0,
+            u8::from(language.node_kind_is_named(id)),
+            u8::from(language.node_kind_is_visible(id)),
+        ]);
+    }
+
+    hasher.write_u16(language.field_count() as u16);
+    for id in field_ids(language) {
+        hasher.write_u16(id);
+        hasher.write(language.field_name_for_id(id).unwrap_or("").as_bytes());
+        hasher.write(&[0]);
+    }
+
+    hasher.0
+}
+
+/// The 64-bit FNV-1a hash, which is stable across Rust versions and platforms
+/// unlike `std`'s hashers.
+pub(crate) struct Fnv1a(pub(crate) u64);
+
+impl Fnv1a {
+    pub(crate) fn new() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    pub(crate) fn write_u16(&mut self, value: u16) {
+        self.write(&value.to_le_bytes());
+    }
+}
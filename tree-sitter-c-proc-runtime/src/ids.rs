@@ -0,0 +1,209 @@
+//! Distinct types for node kind, token and field IDs.
+//!
+//! With a leading `typed` argument, such as `kind!(typed, "if_statement")`,
+//! the macros of `tree-sitter-c-proc` expand to a [`KindId`] for `kind!`, a
+//! [`TokenId`] for `kw!` and a [`FieldId`] for `field!` instead of bare
+//! integers, so comparing a keyword against a named node kind, or a field
+//! against a node kind, is a type error. [`NodeExt`] and [`TreeCursorExt`] read
+//! the same types from a syntax tree.
+//!
+//! `kind!` and `kw!` still work as patterns, since the types are plain tuple
+//! structs, and the types compare with `u16` for code that has raw IDs:
+//!
+//! ```
+//! use tree_sitter_c_proc_runtime::{KindId, NodeExt, TokenId};
+//!
+//! let language = tree_sitter_c::language();
+//! let mut parser = tree_sitter::Parser::new();
+//! parser.set_language(language).unwrap();
+//! let tree = parser.parse("int x;", None).unwrap();
+//! let root = tree.root_node();
+//!
+//! let translation_unit = KindId::new(language.id_for_node_kind("translation_unit", true));
+//! assert_eq!(root.kind_id_typed(), translation_unit);
+//! assert_eq!(translation_unit, root.kind_id());
+//!
+//! let semicolon = root.child(0).unwrap().child(2).unwrap();
+//! assert_eq!(semicolon.token_id_typed(), TokenId(language.id_for_node_kind(";", false)));
+//! ```
+
+use std::fmt;
+use std::num::NonZeroU16;
+
+use tree_sitter::{Node, TreeCursor};
+
+/// The ID of a named node kind, as `Node::kind_id` reports it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KindId(pub u16);
+
+/// The ID of a keyword or other anonymous token, as `Node::kind_id` reports
+/// it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TokenId(pub u16);
+
+/// The ID of a field, as `Language::field_id_for_name` reports it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FieldId(pub NonZeroU16);
+
+impl KindId {
+    /// Wraps a raw node kind ID.
+    pub const fn new(id: u16) -> Self {
+        KindId(id)
+    }
+
+    /// Returns the raw ID, for APIs that take a `u16`.
+    pub const fn raw(self) -> u16 {
+        self.0
+    }
+}
+
+impl TokenId {
+    /// Wraps a raw token ID.
+    pub const fn new(id: u16) -> Self {
+        TokenId(id)
+    }
+
+    /// Returns the raw ID, for APIs that take a `u16`.
+    pub const fn raw(self) -> u16 {
+        self.0
+    }
+}
+
+impl FieldId {
+    /// Wraps a raw field ID.
+    pub const fn new(id: NonZeroU16) -> Self {
+        FieldId(id)
+    }
+
+    /// Wraps a raw field ID, returning `None` for 0, which means "no field".
+    pub const fn from_u16(id: u16) -> Option<Self> {
+        match NonZeroU16::new(id) {
+            Some(id) => Some(FieldId(id)),
+            None => None,
+        }
+    }
+
+    /// Returns the raw ID.
+    pub const fn raw(self) -> NonZeroU16 {
+        self.0
+    }
+
+    /// Returns the raw ID as a `u16`, for APIs such as
+    /// `Node::child_by_field_id`.
+    pub const fn get(self) -> u16 {
+        self.0.get()
+    }
+}
+
+impl PartialEq<u16> for KindId {
+    fn eq(&self, other: &u16) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<KindId> for u16 {
+    fn eq(&self, other: &KindId) -> bool {
+        *self == other.0
+    }
+}
+
+impl PartialEq<u16> for TokenId {
+    fn eq(&self, other: &u16) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<TokenId> for u16 {
+    fn eq(&self, other: &TokenId) -> bool {
+        *self == other.0
+    }
+}
+
+impl PartialEq<u16> for FieldId {
+    fn eq(&self, other: &u16) -> bool {
+        self.0.get() == *other
+    }
+}
+
+impl PartialEq<FieldId> for u16 {
+    fn eq(&self, other: &FieldId) -> bool {
+        *self == other.0.get()
+    }
+}
+
+impl From<KindId> for u16 {
+    fn from(id: KindId) -> u16 {
+        id.0
+    }
+}
+
+impl From<TokenId> for u16 {
+    fn from(id: TokenId) -> u16 {
+        id.0
+    }
+}
+
+impl From<FieldId> for u16 {
+    fn from(id: FieldId) -> u16 {
+        id.0.get()
+    }
+}
+
+impl fmt::Display for KindId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for TokenId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for FieldId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Typed IDs for `tree_sitter::Node`.
+pub trait NodeExt<'tree> {
+    /// Returns the node's kind ID as a [`KindId`], to match against `kind!`.
+    ///
+    /// Anonymous nodes have token IDs, which never equal a `kind!`; use
+    /// [`token_id_typed`](NodeExt::token_id_typed) for them.
+    fn kind_id_typed(&self) -> KindId;
+
+    /// Returns the node's kind ID as a [`TokenId`], to match against `kw!`.
+    fn token_id_typed(&self) -> TokenId;
+
+    /// Returns the first child in the field `field_id`.
+    fn child_by_field_id_typed(&self, field_id: FieldId) -> Option<Node<'tree>>;
+}
+
+impl<'tree> NodeExt<'tree> for Node<'tree> {
+    fn kind_id_typed(&self) -> KindId {
+        KindId(self.kind_id())
+    }
+
+    fn token_id_typed(&self) -> TokenId {
+        TokenId(self.kind_id())
+    }
+
+    fn child_by_field_id_typed(&self, field_id: FieldId) -> Option<Node<'tree>> {
+        self.child_by_field_id(field_id.get())
+    }
+}
+
+/// Typed IDs for `tree_sitter::TreeCursor`.
+pub trait TreeCursorExt {
+    /// Returns the field of the cursor's current node, if it is in one.
+    fn field_id_typed(&self) -> Option<FieldId>;
+}
+
+impl TreeCursorExt for TreeCursor<'_> {
+    fn field_id_typed(&self) -> Option<FieldId> {
+        self.field_id().and_then(FieldId::from_u16)
+    }
+}
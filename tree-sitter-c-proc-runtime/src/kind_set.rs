@@ -0,0 +1,211 @@
+//! A constant set of node kind IDs.
+//!
+//! Checking whether a node is one of thirty statement kinds with `==` or a
+//! `match` compares the ID against each of them. A [`KindSet`] stores one bit
+//! per symbol of the grammar, so membership is a shift and a mask, and sets
+//! combine with constant functions:
+//!
+//! ```
+//! use tree_sitter_c_proc_runtime::KindSet;
+//!
+//! const LOOPS: KindSet<2> = KindSet::from_ids(&[3, 70, 71]);
+//! const BRANCHES: KindSet<2> = KindSet::from_ids(&[5, 70]);
+//! const CONTROL_FLOW: KindSet<2> = LOOPS.union(BRANCHES);
+//!
+//! assert!(CONTROL_FLOW.contains(5));
+//! assert!(!CONTROL_FLOW.contains(6));
+//! assert_eq!(LOOPS.intersection(BRANCHES).iter().collect::<Vec<_>>(), [70]);
+//! assert_eq!(LOOPS.difference(BRANCHES).len(), 2);
+//! ```
+//!
+//! `kind_set!` builds a set from node kind names, sized to the grammar's
+//! symbol count.
+
+use std::fmt;
+
+/// A set of node kind IDs of a grammar with fewer than `WORDS * 64` symbols.
+///
+/// Besides the symbols, a set can contain `ERROR`, whose ID is `u16::MAX`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KindSet<const WORDS: usize> {
+    words: [u64; WORDS],
+    error: bool,
+}
+
+impl<const WORDS: usize> KindSet<WORDS> {
+    /// The set without any kind.
+    pub const EMPTY: Self = KindSet {
+        words: [0; WORDS],
+        error: false,
+    };
+
+    /// Returns the set of the given IDs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an ID other than `u16::MAX` doesn't fit in the set, which is a
+    /// compile error when the set is built in a constant.
+    pub const fn from_ids(ids: &[u16]) -> Self {
+        let mut set = Self::EMPTY;
+        let mut index = 0;
+        while index < ids.len() {
+            set = set.with(ids[index]);
+            index += 1;
+        }
+        set
+    }
+
+    /// Returns the set with `id` added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` isn't `u16::MAX` and doesn't fit in the set.
+    pub const fn with(mut self, id: u16) -> Self {
+        if id == u16::MAX {
+            self.error = true;
+        } else {
+            let (word, bit) = Self::position(id);
+            assert!(word < WORDS, "node kind ID out of range for this KindSet");
+            self.words[word] |= bit;
+        }
+        self
+    }
+
+    /// Returns the set with `id` removed.
+    pub const fn without(mut self, id: u16) -> Self {
+        if id == u16::MAX {
+            self.error = false;
+        } else {
+            let (word, bit) = Self::position(id);
+            if word < WORDS {
+                self.words[word] &= !bit;
+            }
+        }
+        self
+    }
+
+    /// Returns whether the set contains `id`, as `Node::kind_id` reports it.
+    pub const fn contains(&self, id: u16) -> bool {
+        if id == u16::MAX {
+            return self.error;
+        }
+        let (word, bit) = Self::position(id);
+        word < WORDS && self.words[word] & bit != 0
+    }
+
+    /// Returns the kinds that are in either set.
+    pub const fn union(mut self, other: Self) -> Self {
+        let mut word = 0;
+        while word < WORDS {
+            self.words[word] |= other.words[word];
+            word += 1;
+        }
+        self.error |= other.error;
+        self
+    }
+
+    /// Returns the kinds that are in both sets.
+    pub const fn intersection(mut self, other: Self) -> Self {
+        let mut word = 0;
+        while word < WORDS {
+            self.words[word] &= other.words[word];
+            word += 1;
+        }
+        self.error &= other.error;
+        self
+    }
+
+    /// Returns the kinds that are in this set but not in `other`.
+    pub const fn difference(mut self, other: Self) -> Self {
+        let mut word = 0;
+        while word < WORDS {
+            self.words[word] &= !other.words[word];
+            word += 1;
+        }
+        self.error &= !other.error;
+        self
+    }
+
+    /// Returns whether the set contains no kind.
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of kinds in the set.
+    pub const fn len(&self) -> usize {
+        let mut len = self.error as usize;
+        let mut word = 0;
+        while word < WORDS {
+            len += self.words[word].count_ones() as usize;
+            word += 1;
+        }
+        len
+    }
+
+    /// Returns an iterator over the IDs in the set, in ascending order.
+    pub fn iter(&self) -> Iter<'_, WORDS> {
+        Iter { set: self, next: 0 }
+    }
+
+    /// Returns the word and the bit of `id`.
+    const fn position(id: u16) -> (usize, u64) {
+        (id as usize / 64, 1 << (id % 64))
+    }
+}
+
+impl<const WORDS: usize> Default for KindSet<WORDS> {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+impl<const WORDS: usize> fmt::Debug for KindSet<WORDS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, const WORDS: usize> IntoIterator for &'a KindSet<WORDS> {
+    type Item = u16;
+    type IntoIter = Iter<'a, WORDS>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the IDs in a [`KindSet`], in ascending order, with
+/// `ERROR` last.
+#[derive(Clone, Debug)]
+pub struct Iter<'a, const WORDS: usize> {
+    set: &'a KindSet<WORDS>,
+    /// The smallest bit position that hasn't been visited, `WORDS * 64` for
+    /// `ERROR` and past it when done.
+    next: usize,
+}
+
+impl<const WORDS: usize> Iterator for Iter<'_, WORDS> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        while self.next < WORDS * 64 {
+            let word = self.next / 64;
+            // The bits of the word that haven't been visited
+            let remaining = self.set.words[word] & (u64::MAX << (self.next % 64));
+            if remaining == 0 {
+                self.next = (word + 1) * 64;
+                continue;
+            }
+            let id = word * 64 + remaining.trailing_zeros() as usize;
+            self.next = id + 1;
+            return Some(id as u16);
+        }
+        if self.next == WORDS * 64 {
+            self.next += 1;
+            if self.set.error {
+                return Some(u16::MAX);
+            }
+        }
+        None
+    }
+}
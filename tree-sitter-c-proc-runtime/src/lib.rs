@@ -0,0 +1,21 @@
+//! Runtime support for the values the `tree-sitter-c-proc` macros expand to.
+//!
+//! The macros bake node kind and field IDs into the calling crate at compile
+//! time. This crate holds what the calling crate needs at runtime to work with
+//! them, such as distinct types for the IDs, checking that the grammar it
+//! links is the one the IDs came from, looking up names that only arrive at
+//! runtime, walking trees without recursion and finding the name a C
+//! declarator declares.
+
+pub mod declarator;
+pub mod fingerprint;
+pub mod ids;
+pub mod kind_set;
+pub mod symbol_table;
+pub mod traversal;
+
+pub use fingerprint::{Fingerprint, Mismatch};
+pub use ids::{FieldId, KindId, NodeExt, TokenId, TreeCursorExt};
+pub use kind_set::KindSet;
+pub use symbol_table::SymbolTable;
+pub use traversal::KindFilter;
@@ -0,0 +1,275 @@
+//! Static name and ID tables of a grammar, for lookups without a `Language`.
+//!
+//! `Language::id_for_node_kind` and `Language::field_id_for_name` scan the
+//! grammar's symbol table across FFI on every call. `symbol_table!()` snapshots
+//! the tables at compile time instead: names map to IDs through a perfect hash,
+//! built by [`layout`], and IDs index plain arrays. Every lookup is O(1) and
+//! allocation-free.
+//!
+//! ```
+//! use tree_sitter_c_proc_runtime::symbol_table;
+//!
+//! let keys = [("identifier", true), ("if", false), ("if", true)];
+//! let layout = symbol_table::layout(&keys);
+//!
+//! // Every key gets a slot of its own
+//! let mut slots = layout.order.clone();
+//! slots.sort();
+//! assert_eq!(slots, [0, 1, 2]);
+//! ```
+
+use crate::fingerprint::{Field, Fnv1a};
+
+/// The symbol and field tables of a grammar, as `symbol_table!()` recorded
+/// them at compile time.
+///
+/// The methods mirror the lookups of `tree_sitter::Language`, except that
+/// names that aren't part of the grammar return `None` instead of 0.
+#[derive(Clone, Copy, Debug)]
+pub struct SymbolTable {
+    /// Every visible node kind, keyed by name and named-ness.
+    pub kinds: Map<NodeKind>,
+    /// Every symbol, visible or not, indexed by its ID.
+    pub symbols: &'static [Symbol],
+    /// Every field, keyed by name.
+    pub fields: Map<Field>,
+    /// Every field name, indexed by the field's ID minus 1.
+    pub field_names: &'static [&'static str],
+}
+
+/// A visible node kind and the symbol IDs that carry its name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodeKind {
+    pub name: &'static str,
+    pub named: bool,
+    /// The ID `Language::id_for_node_kind` returns for the name, which is the
+    /// one `Node::kind_id` reports.
+    pub id: u16,
+    /// Every ID carrying the name, in ID order.
+    pub ids: &'static [u16],
+}
+
+/// A symbol of the grammar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: &'static str,
+    pub named: bool,
+    /// Whether nodes of the symbol appear in syntax trees.
+    pub visible: bool,
+}
+
+/// A static map from names to entries, without collisions.
+///
+/// The hash of a name selects one of the `displacements`, which moves the name
+/// to its slot in `entries`. [`layout`] chooses the seed and displacements so
+/// that no two names share a slot.
+#[derive(Clone, Copy, Debug)]
+pub struct Map<T: 'static> {
+    pub seed: u64,
+    pub displacements: &'static [(u32, u32)],
+    pub entries: &'static [T],
+}
+
+/// The name of the `ERROR` kind, which isn't part of the symbol table.
+const ERROR: &str = "ERROR";
+
+impl SymbolTable {
+    /// Returns the ID of the node kind with the given name and named-ness, the
+    /// one `Node::kind_id` reports for nodes of that kind.
+    pub fn id_for_node_kind(&self, name: &str, named: bool) -> Option<u16> {
+        self.node_kind(name, named).map(|kind| kind.id)
+    }
+
+    /// Returns every symbol ID carrying the given name and named-ness, in ID
+    /// order, or an empty slice.
+    ///
+    /// A name belongs to several IDs when a rule is aliased or a token is
+    /// reused in another context.
+    pub fn ids_for_node_kind(&self, name: &str, named: bool) -> &'static [u16] {
+        self.node_kind(name, named).map_or(&[], |kind| kind.ids)
+    }
+
+    /// Returns the node kind with the given name and named-ness.
+    pub fn node_kind(&self, name: &str, named: bool) -> Option<&'static NodeKind> {
+        self.kinds
+            .get(name, named, |kind| kind.name == name && kind.named == named)
+    }
+
+    /// Returns the name of the symbol with the given ID.
+    pub fn node_kind_for_id(&self, id: u16) -> Option<&'static str> {
+        if id == u16::MAX {
+            return Some(ERROR);
+        }
+        self.symbol(id).map(|symbol| symbol.name)
+    }
+
+    /// Returns the symbol with the given ID, if the grammar has it.
+    ///
+    /// `ERROR`, with the ID `u16::MAX`, isn't part of the symbol table.
+    pub fn symbol(&self, id: u16) -> Option<&'static Symbol> {
+        self.symbols.get(usize::from(id))
+    }
+
+    /// Returns whether the symbol with the given ID is named, and `false` for
+    /// IDs the grammar doesn't have.
+    pub fn node_kind_is_named(&self, id: u16) -> bool {
+        id == u16::MAX || self.symbol(id).is_some_and(|symbol| symbol.named)
+    }
+
+    /// Returns whether the symbol with the given ID is visible, and `false`
+    /// for IDs the grammar doesn't have.
+    pub fn node_kind_is_visible(&self, id: u16) -> bool {
+        id == u16::MAX || self.symbol(id).is_some_and(|symbol| symbol.visible)
+    }
+
+    /// Returns the number of symbols in the grammar.
+    pub fn node_kind_count(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Returns the ID of the field with the given name.
+    pub fn field_id_for_name(&self, name: &str) -> Option<u16> {
+        self.fields
+            .get(name, true, |field| field.name == name)
+            .map(|field| field.id)
+    }
+
+    /// Returns the name of the field with the given ID.
+    pub fn field_name_for_id(&self, id: u16) -> Option<&'static str> {
+        // Fields are stored by ID, which starts at 1
+        let index = usize::from(id).checked_sub(1)?;
+        self.field_names.get(index).copied()
+    }
+
+    /// Returns the number of fields in the grammar.
+    pub fn field_count(&self) -> usize {
+        self.field_names.len()
+    }
+}
+
+impl<T> Map<T> {
+    /// Returns the entry in the slot of `(name, named)`, if `matches` accepts
+    /// it.
+    fn get(&self, name: &str, named: bool, matches: impl Fn(&T) -> bool) -> Option<&'static T> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let hashes = Hashes::new(self.seed, name, named);
+        let displacement = self.displacements[hashes.bucket(self.displacements.len())];
+        let entry = &self.entries[hashes.slot(displacement, self.entries.len())];
+        matches(entry).then_some(entry)
+    }
+}
+
+/// The seed and displacements of a [`Map`] over a set of keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    pub seed: u64,
+    pub displacements: Vec<(u32, u32)>,
+    /// The index of the key in each slot of the map's entries.
+    pub order: Vec<usize>,
+}
+
+/// The average number of keys sharing a displacement.
+const KEYS_PER_BUCKET: usize = 5;
+
+/// Finds a seed and displacements that give each key its own slot.
+///
+/// The keys are `(name, named)` pairs and must be distinct. Field maps use
+/// `true` for `named`. `symbol_table!()` calls this at compile time.
+pub fn layout(keys: &[(&str, bool)]) -> Layout {
+    (0..)
+        .find_map(|seed| try_layout(seed, keys))
+        .expect("some seed places every key")
+}
+
+fn try_layout(seed: u64, keys: &[(&str, bool)]) -> Option<Layout> {
+    let hashes: Vec<Hashes> = keys
+        .iter()
+        .map(|&(name, named)| Hashes::new(seed, name, named))
+        .collect();
+
+    let bucket_count = keys.len().div_ceil(KEYS_PER_BUCKET);
+    let mut buckets = vec![Vec::new(); bucket_count];
+    for (index, hashes) in hashes.iter().enumerate() {
+        buckets[hashes.bucket(bucket_count)].push(index);
+    }
+
+    // Place the largest buckets first, while most slots are still free
+    let mut bucket_order: Vec<usize> = (0..bucket_count).collect();
+    bucket_order.sort_by_key(|&bucket| std::cmp::Reverse(buckets[bucket].len()));
+
+    let slot_count = keys.len();
+    let mut slots: Vec<Option<usize>> = vec![None; slot_count];
+    let mut displacements = vec![(0, 0); bucket_count];
+    let mut placed = Vec::with_capacity(KEYS_PER_BUCKET);
+
+    'buckets: for bucket in bucket_order {
+        for d1 in 0..slot_count as u32 {
+            'displacement: for d2 in 0..slot_count as u32 {
+                placed.clear();
+                for &index in &buckets[bucket] {
+                    let slot = hashes[index].slot((d1, d2), slot_count);
+                    if slots[slot].is_some() || placed.iter().any(|&(other, _)| other == slot) {
+                        continue 'displacement;
+                    }
+                    placed.push((slot, index));
+                }
+
+                for &(slot, index) in &placed {
+                    slots[slot] = Some(index);
+                }
+                displacements[bucket] = (d1, d2);
+                continue 'buckets;
+            }
+        }
+        return None;
+    }
+
+    Some(Layout {
+        seed,
+        displacements,
+        order: slots.into_iter().map(Option::unwrap).collect(),
+    })
+}
+
+/// The three parts of a key's hash: one selects the displacement, the other
+/// two are displaced into a slot.
+struct Hashes {
+    g: u32,
+    f1: u32,
+    f2: u32,
+}
+
+impl Hashes {
+    fn new(seed: u64, name: &str, named: bool) -> Self {
+        let mut hasher = Fnv1a::new();
+        hasher.write(&seed.to_le_bytes());
+        hasher.write(name.as_bytes());
+        hasher.write(&[0, u8::from(named)]);
+
+        // FNV-1a mixes its last bytes poorly, so finish with splitmix64's
+        // finalizer before splitting the hash
+        let mut hash = hasher.0;
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        hash ^= hash >> 31;
+
+        Hashes {
+            g: (hash >> 42) as u32,
+            f1: (hash >> 21) as u32 & 0x1f_ffff,
+            f2: hash as u32 & 0x1f_ffff,
+        }
+    }
+
+    fn bucket(&self, bucket_count: usize) -> usize {
+        self.g as usize % bucket_count
+    }
+
+    fn slot(&self, (d1, d2): (u32, u32), slot_count: usize) -> usize {
+        let slot = d2
+            .wrapping_add(self.f1.wrapping_mul(d1))
+            .wrapping_add(self.f2);
+        slot as usize % slot_count
+    }
+}
@@ -0,0 +1,315 @@
+//! Iterators over the nodes around a node, without recursion.
+//!
+//! Walking a tree with a recursive function overflows the stack on deeply
+//! nested input, such as a long chain of `else if`s or a generated expression.
+//! These iterators move a single `TreeCursor` instead, so they use the same
+//! memory however deep the tree is.
+//!
+//! ```
+//! use tree_sitter_c_proc_runtime::traversal::{descendants, descendants_of_kind};
+//!
+//! let language = tree_sitter_c::language();
+//! let mut parser = tree_sitter::Parser::new();
+//! parser.set_language(language).unwrap();
+//! let tree = parser.parse("int f(void) { return g(h(1)); }", None).unwrap();
+//!
+//! let calls: Vec<_> = descendants(tree.root_node())
+//!     .filter(|node| node.kind() == "call_expression")
+//!     .collect();
+//! assert_eq!(calls.len(), 2);
+//!
+//! // Kinds are filtered by a `KindSet`, an ID or a predicate on the kind ID
+//! let number_literal = language.id_for_node_kind("number_literal", true);
+//! let numbers: Vec<_> = descendants_of_kind(tree.root_node(), number_literal).collect();
+//! assert_eq!(numbers[0].kind(), "number_literal");
+//! ```
+//!
+//! With `tree-sitter-c-proc`, `kind!` selects a single kind, and `kind_set!`
+//! several kinds or the subtypes of a supertype:
+//! `descendants_of_kind(node, kind_set!("_statement"))`.
+
+use tree_sitter::{Node, TreeCursor};
+
+use crate::ids::KindId;
+use crate::kind_set::KindSet;
+
+/// Selects nodes by their kind ID.
+pub trait KindFilter {
+    /// Returns whether nodes with the kind ID `kind_id` are selected.
+    fn matches(&self, kind_id: u16) -> bool;
+}
+
+impl<const WORDS: usize> KindFilter for KindSet<WORDS> {
+    fn matches(&self, kind_id: u16) -> bool {
+        self.contains(kind_id)
+    }
+}
+
+impl KindFilter for u16 {
+    fn matches(&self, kind_id: u16) -> bool {
+        *self == kind_id
+    }
+}
+
+impl KindFilter for KindId {
+    fn matches(&self, kind_id: u16) -> bool {
+        *self == kind_id
+    }
+}
+
+impl<F: Fn(u16) -> bool> KindFilter for F {
+    fn matches(&self, kind_id: u16) -> bool {
+        self(kind_id)
+    }
+}
+
+/// The filter of [`descendants`], which selects every node.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AnyKind;
+
+impl KindFilter for AnyKind {
+    fn matches(&self, _kind_id: u16) -> bool {
+        true
+    }
+}
+
+/// The order in which [`Descendants`] visits nodes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Order {
+    /// Every node before its children.
+    #[default]
+    Pre,
+    /// Every node after its children.
+    Post,
+}
+
+/// Returns an iterator over `node` and all of its descendants, named or
+/// anonymous, in pre-order.
+pub fn descendants(node: Node<'_>) -> Descendants<'_, AnyKind> {
+    descendants_of_kind(node, AnyKind)
+}
+
+/// Returns an iterator over `node` and those of its descendants whose kind
+/// matches `filter`, in pre-order.
+///
+/// Nodes of other kinds are still walked through, so matches nested in them
+/// are found.
+pub fn descendants_of_kind<F: KindFilter>(node: Node<'_>, filter: F) -> Descendants<'_, F> {
+    Descendants {
+        cursor: node.walk(),
+        filter,
+        prune: |_| false,
+        order: Order::Pre,
+        state: State::Start,
+        skip_subtree: false,
+    }
+}
+
+/// An iterator over the nodes of a subtree. See [`descendants`] and
+/// [`descendants_of_kind`].
+pub struct Descendants<'tree, F, P = fn(&Node<'tree>) -> bool> {
+    cursor: TreeCursor<'tree>,
+    filter: F,
+    prune: P,
+    order: Order,
+    state: State,
+    skip_subtree: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// Nothing has been visited.
+    Start,
+    /// The cursor is at the node visited last.
+    Visited,
+    Done,
+}
+
+impl<'tree, F: KindFilter, P: FnMut(&Node<'tree>) -> bool> Descendants<'tree, F, P> {
+    /// Sets the order in which nodes are visited.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the iteration has started.
+    pub fn order(mut self, order: Order) -> Self {
+        assert_eq!(self.state, State::Start, "the iteration has started");
+        self.order = order;
+        self
+    }
+
+    /// Visits every node after its children instead, the same as
+    /// `order(Order::Post)`.
+    pub fn post_order(self) -> Self {
+        self.order(Order::Post)
+    }
+
+    /// Doesn't walk into the children of nodes for which `prune` returns
+    /// `true`. The nodes themselves are still visited if their kind matches.
+    pub fn prune<Q: FnMut(&Node<'tree>) -> bool>(self, prune: Q) -> Descendants<'tree, F, Q> {
+        Descendants {
+            cursor: self.cursor,
+            filter: self.filter,
+            prune,
+            order: self.order,
+            state: self.state,
+            skip_subtree: self.skip_subtree,
+        }
+    }
+
+    /// Skips the children of the node returned last, in pre-order.
+    ///
+    /// In post-order the children have been visited before the node, so this
+    /// does nothing; use [`prune`](Descendants::prune) instead.
+    pub fn skip_subtree(&mut self) {
+        self.skip_subtree = true;
+    }
+
+    /// Moves the cursor to the next node in pre-order, returning `false` at the
+    /// end.
+    fn advance_pre_order(&mut self) -> bool {
+        let node = self.cursor.node();
+        let skip_subtree = std::mem::take(&mut self.skip_subtree);
+        if !skip_subtree && !(self.prune)(&node) && self.cursor.goto_first_child() {
+            return true;
+        }
+        // The cursor can't leave the node it was created for, so reaching it
+        // again ends the walk
+        loop {
+            if self.cursor.goto_next_sibling() {
+                return true;
+            }
+            if !self.cursor.goto_parent() {
+                return false;
+            }
+        }
+    }
+
+    /// Moves the cursor to the next node in post-order, returning `false` at
+    /// the end.
+    fn advance_post_order(&mut self) -> bool {
+        let found = match self.state {
+            State::Start => true,
+            _ => self.cursor.goto_next_sibling(),
+        };
+        if !found {
+            return self.cursor.goto_parent();
+        }
+        // Descend to the first leaf of the new subtree
+        loop {
+            let node = self.cursor.node();
+            if (self.prune)(&node) || !self.cursor.goto_first_child() {
+                return true;
+            }
+        }
+    }
+}
+
+impl<'tree, F: KindFilter, P: FnMut(&Node<'tree>) -> bool> Iterator for Descendants<'tree, F, P> {
+    type Item = Node<'tree>;
+
+    fn next(&mut self) -> Option<Node<'tree>> {
+        loop {
+            let found = match (self.state, self.order) {
+                (State::Done, _) => return None,
+                (State::Start, Order::Pre) => true,
+                (_, Order::Pre) => self.advance_pre_order(),
+                (_, Order::Post) => self.advance_post_order(),
+            };
+            if !found {
+                self.state = State::Done;
+                return None;
+            }
+            self.state = State::Visited;
+
+            let node = self.cursor.node();
+            if self.filter.matches(node.kind_id()) {
+                return Some(node);
+            }
+        }
+    }
+}
+
+/// Returns an iterator over the children of `node` in the field `field_id`,
+/// such as `field!("argument")`.
+pub fn children_by_field(node: Node<'_>, field_id: impl Into<u16>) -> ChildrenByField<'_> {
+    let mut cursor = node.walk();
+    let started = cursor.goto_first_child();
+    ChildrenByField {
+        cursor,
+        field_id: field_id.into(),
+        done: !started,
+    }
+}
+
+/// An iterator over the children of a node in one field. See
+/// [`children_by_field`].
+pub struct ChildrenByField<'tree> {
+    cursor: TreeCursor<'tree>,
+    field_id: u16,
+    done: bool,
+}
+
+impl<'tree> Iterator for ChildrenByField<'tree> {
+    type Item = Node<'tree>;
+
+    fn next(&mut self) -> Option<Node<'tree>> {
+        while !self.done {
+            let node = self.cursor.node();
+            let field_id = self.cursor.field_id();
+            self.done = !self.cursor.goto_next_sibling();
+            if field_id == Some(self.field_id) {
+                return Some(node);
+            }
+        }
+        None
+    }
+}
+
+/// Returns an iterator over the parent of `node`, its parent and so on, up to
+/// the root.
+///
+/// `Node::parent` walks down from the root, so every step costs time in
+/// proportion to the depth of the node, but no stack.
+pub fn ancestors(node: Node<'_>) -> Ancestors<'_> {
+    Ancestors {
+        next: node.parent(),
+    }
+}
+
+/// An iterator over the ancestors of a node. See [`ancestors`].
+pub struct Ancestors<'tree> {
+    next: Option<Node<'tree>>,
+}
+
+impl<'tree> Iterator for Ancestors<'tree> {
+    type Item = Node<'tree>;
+
+    fn next(&mut self) -> Option<Node<'tree>> {
+        let node = self.next?;
+        self.next = node.parent();
+        Some(node)
+    }
+}
+
+/// Returns an iterator over the siblings after `node`, named or anonymous, in
+/// order.
+pub fn siblings(node: Node<'_>) -> Siblings<'_> {
+    Siblings {
+        next: node.next_sibling(),
+    }
+}
+
+/// An iterator over the following siblings of a node. See [`siblings`].
+pub struct Siblings<'tree> {
+    next: Option<Node<'tree>>,
+}
+
+impl<'tree> Iterator for Siblings<'tree> {
+    type Item = Node<'tree>;
+
+    fn next(&mut self) -> Option<Node<'tree>> {
+        let node = self.next?;
+        self.next = node.next_sibling();
+        Some(node)
+    }
+}
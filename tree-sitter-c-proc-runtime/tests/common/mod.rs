@@ -0,0 +1,9 @@
+// Helpers shared by the tests that parse C with tree-sitter-c
+use tree_sitter::{Parser, Tree};
+
+/// Parses `code` as C.
+pub fn parse(code: &str) -> Tree {
+    let mut parser = Parser::new();
+    parser.set_language(tree_sitter_c::language()).unwrap();
+    parser.parse(code, None).unwrap()
+}
@@ -0,0 +1,143 @@
+// Tests for the C declarator resolver, with tree-sitter-c
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_c_proc_runtime::declarator::{resolve, Modifier};
+use tree_sitter_c_proc_runtime::traversal::{children_by_field, descendants};
+
+/// Resolves every node in a `declarator` field in `code`, in pre-order, to its
+/// name and modifiers.
+fn resolve_all(code: &str) -> Vec<(Option<String>, Vec<&'static str>)> {
+    let tree = parse(code);
+    let field_id = tree_sitter_c::language()
+        .field_id_for_name("declarator")
+        .unwrap();
+    // Nested declarators are in a `declarator` field themselves, so only
+    // those of other nodes start a declarator
+    let declarators: Vec<Node> = descendants(tree.root_node())
+        .filter(|node| !node.kind().ends_with("declarator"))
+        .flat_map(|node| children_by_field(node, field_id))
+        .collect();
+
+    declarators
+        .into_iter()
+        .map(|declarator| {
+            let resolved = resolve(declarator);
+            let name = resolved
+                .name
+                .map(|name| name.utf8_text(code.as_bytes()).unwrap().to_string());
+            let modifiers = resolved
+                .modifiers
+                .iter()
+                .map(|modifier| match modifier {
+                    Modifier::Pointer(_) => "pointer",
+                    Modifier::Array(_) => "array",
+                    Modifier::Function(_) => "function",
+                })
+                .collect();
+            (name, modifiers)
+        })
+        .collect()
+}
+
+fn named(name: &str, modifiers: &[&'static str]) -> (Option<String>, Vec<&'static str>) {
+    (Some(name.to_string()), modifiers.to_vec())
+}
+
+#[test]
+fn test_declarations() {
+    assert_eq!(
+        resolve_all("Point* create_point(int x) { return 0; }"),
+        [
+            named("create_point", &["pointer", "function"]),
+            named("x", &[])
+        ]
+    );
+    assert_eq!(
+        resolve_all("int *table[8], (*handler)(int), (x) = 1;"),
+        [
+            named("table", &["pointer", "array"]),
+            named("handler", &["function", "pointer"]),
+            named("x", &[]),
+        ]
+    );
+    assert_eq!(
+        resolve_all("struct s { char *name; int (*get)(void); };"),
+        [
+            named("name", &["pointer"]),
+            named("get", &["function", "pointer"]),
+        ]
+    );
+    assert_eq!(
+        resolve_all("typedef int (*callback)(void *data);"),
+        [
+            named("callback", &["function", "pointer"]),
+            named("data", &["pointer"]),
+        ]
+    );
+    assert_eq!(resolve_all("int x [[deprecated]];"), [named("x", &[])]);
+
+    // tree-sitter-c parses names such as `size_t` as primitive types, even
+    // where a typedef declares them
+    assert_eq!(
+        resolve_all("typedef unsigned long size_t, *psize_t;"),
+        [named("size_t", &[]), named("psize_t", &["pointer"])]
+    );
+}
+
+#[test]
+fn test_abstract_declarators() {
+    // Casts and `sizeof` take type descriptors, and parameters may be unnamed
+    assert_eq!(
+        resolve_all("void f(int *, char [], void (*)(void)) { g((int (*)[4]) p); }"),
+        [
+            named("f", &["function"]),
+            (None, vec!["pointer"]),
+            (None, vec!["array"]),
+            (None, vec!["function", "pointer"]),
+            (None, vec!["array", "pointer"]),
+        ]
+    );
+}
+
+#[test]
+fn test_declarator_kinds() {
+    let code = "Point* create_point(int x);";
+    let tree = parse(code);
+    let declarator = tree
+        .root_node()
+        .child(0)
+        .unwrap()
+        .child_by_field_name("declarator")
+        .unwrap();
+
+    let resolved = resolve(declarator);
+    assert!(resolved.is_function());
+    assert!(!resolved.is_pointer());
+    assert_eq!(resolved.modifiers[0].node(), declarator);
+    assert_eq!(resolved.modifiers[1].node().kind(), "function_declarator");
+
+    // A name resolves to itself
+    let name = resolved.name.unwrap();
+    assert_eq!(resolve(name).name, Some(name));
+    assert!(resolve(name).modifiers.is_empty());
+}
+
+#[test]
+fn test_deep_nesting() {
+    let depth = 10_000;
+    let code = format!("int {}x;", "*".repeat(depth));
+    let tree = parse(&code);
+    let declarator = tree
+        .root_node()
+        .child(0)
+        .unwrap()
+        .child_by_field_name("declarator")
+        .unwrap();
+
+    let resolved = resolve(declarator);
+    assert_eq!(resolved.name.unwrap().kind(), "identifier");
+    assert_eq!(resolved.modifiers.len(), depth);
+    assert!(resolved.is_pointer());
+}
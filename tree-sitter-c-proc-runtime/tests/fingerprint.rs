@@ -0,0 +1,96 @@
+// Tests for verifying a Language against a fingerprint, with tree-sitter-c
+use tree_sitter_c_proc_runtime::fingerprint::{self, Field, Kind, MovedField, MovedKind};
+use tree_sitter_c_proc_runtime::Fingerprint;
+
+/// A fingerprint of a grammar where `if_statement` and `declarator` had other
+/// IDs, and which had a `lambda_expression` kind and a `captures` field that
+/// tree-sitter-c doesn't have.
+const OTHER_GRAMMAR: Fingerprint = Fingerprint {
+    hash: 0,
+    kinds: &[
+        Kind {
+            name: "if_statement",
+            named: true,
+            ids: &[1000],
+        },
+        Kind {
+            name: "lambda_expression",
+            named: true,
+            ids: &[1001],
+        },
+    ],
+    fields: &[
+        Field {
+            name: "declarator",
+            id: 1000,
+        },
+        Field {
+            name: "captures",
+            id: 1001,
+        },
+    ],
+};
+
+#[test]
+fn test_hash() {
+    let language = tree_sitter_c::language();
+
+    // The hash is deterministic
+    assert_eq!(fingerprint::hash(&language), fingerprint::hash(&language));
+
+    let fingerprint = Fingerprint {
+        hash: fingerprint::hash(&language),
+        ..OTHER_GRAMMAR
+    };
+    assert_eq!(fingerprint.verify_language(&language), Ok(()));
+}
+
+#[test]
+fn test_mismatch() {
+    let language = tree_sitter_c::language();
+    let mismatch = OTHER_GRAMMAR.verify_language(&language).unwrap_err();
+
+    assert_eq!(mismatch.expected, 0);
+    assert_eq!(mismatch.found, fingerprint::hash(&language));
+
+    let if_statement = language.id_for_node_kind("if_statement", true);
+    assert_eq!(
+        mismatch.moved_kinds,
+        [
+            MovedKind {
+                name: "if_statement",
+                named: true,
+                expected: &[1000],
+                found: vec![if_statement],
+            },
+            MovedKind {
+                name: "lambda_expression",
+                named: true,
+                expected: &[1001],
+                found: vec![],
+            },
+        ]
+    );
+    assert_eq!(
+        mismatch.moved_fields,
+        [
+            MovedField {
+                name: "declarator",
+                expected: 1000,
+                found: language.field_id_for_name("declarator"),
+            },
+            MovedField {
+                name: "captures",
+                expected: 1001,
+                found: None,
+            },
+        ]
+    );
+
+    let message = mismatch.to_string();
+    assert!(message.contains(&format!(
+        "node kind if_statement moved from [1000] to [{if_statement}]"
+    )));
+    assert!(message.contains("node kind lambda_expression [1001] is gone"));
+    assert!(message.contains("field captures (1001) is gone"));
+}
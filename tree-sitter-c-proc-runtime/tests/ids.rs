@@ -0,0 +1,57 @@
+// Tests for the typed IDs and the Node and TreeCursor extensions, with
+// tree-sitter-c
+mod common;
+
+use std::num::NonZeroU16;
+
+use common::parse;
+use tree_sitter_c_proc_runtime::{FieldId, KindId, NodeExt, TokenId, TreeCursorExt};
+
+#[test]
+fn test_ids() {
+    const IF_STATEMENT: KindId = KindId::new(1000);
+    const IF_STATEMENT_ID: u16 = IF_STATEMENT.raw();
+    assert_eq!(IF_STATEMENT_ID, 1000);
+    assert_eq!(IF_STATEMENT, 1000);
+    assert_eq!(1000, IF_STATEMENT);
+    assert_eq!(u16::from(TokenId::new(7)), 7);
+
+    // 0 means "no field"
+    const BODY: Option<FieldId> = FieldId::from_u16(3);
+    assert_eq!(FieldId::from_u16(0), None);
+    let body = BODY.unwrap();
+    assert_eq!(body.raw(), NonZeroU16::new(3).unwrap());
+    assert_eq!(body.get(), 3);
+    assert_eq!(body, 3);
+    assert_eq!(body.to_string(), "3");
+}
+
+#[test]
+fn test_node_ext() {
+    let language = tree_sitter_c::language();
+    let tree = parse("int main(void) { return 0; }");
+
+    let function = tree.root_node().child(0).unwrap();
+    assert_eq!(function.kind_id_typed(), function.kind_id());
+
+    let body_id = language.field_id_for_name("body").unwrap();
+    let body = function
+        .child_by_field_id_typed(FieldId::from_u16(body_id).unwrap())
+        .unwrap();
+    assert_eq!(body.kind(), "compound_statement");
+
+    let brace = body.child(0).unwrap();
+    assert_eq!(
+        brace.token_id_typed(),
+        TokenId::new(language.id_for_node_kind("{", false))
+    );
+
+    let mut cursor = function.walk();
+    assert!(cursor.goto_first_child());
+    assert_eq!(
+        cursor.field_id_typed(),
+        FieldId::from_u16(language.field_id_for_name("type").unwrap())
+    );
+    while cursor.goto_next_sibling() {}
+    assert_eq!(cursor.field_id_typed(), FieldId::from_u16(body_id));
+}
@@ -0,0 +1,56 @@
+// Tests for the constant kind sets
+use tree_sitter_c_proc_runtime::KindSet;
+
+const EVENS: KindSet<2> = KindSet::from_ids(&[0, 2, 64, 126]);
+const SMALL: KindSet<2> = KindSet::from_ids(&[0, 1, 2, 3]);
+
+#[test]
+fn test_operations() {
+    assert_eq!(
+        EVENS.union(SMALL).iter().collect::<Vec<_>>(),
+        [0, 1, 2, 3, 64, 126]
+    );
+    assert_eq!(EVENS.intersection(SMALL).iter().collect::<Vec<_>>(), [0, 2]);
+    assert_eq!(
+        EVENS.difference(SMALL).iter().collect::<Vec<_>>(),
+        [64, 126]
+    );
+
+    const WITHOUT_64: KindSet<2> = EVENS.without(64).with(127);
+    assert!(!WITHOUT_64.contains(64));
+    assert!(WITHOUT_64.contains(127));
+
+    // IDs past the end of the set are never in it
+    assert!(!EVENS.contains(128));
+    assert_eq!(EVENS.without(1000), EVENS);
+
+    assert!(KindSet::<2>::EMPTY.is_empty());
+    assert_eq!(KindSet::<2>::default(), KindSet::EMPTY);
+    assert_eq!(EVENS.len(), 4);
+    assert_eq!(format!("{SMALL:?}"), "{0, 1, 2, 3}");
+}
+
+#[test]
+fn test_error() {
+    const WITH_ERROR: KindSet<2> = SMALL.with(u16::MAX);
+
+    assert!(WITH_ERROR.contains(u16::MAX));
+    assert!(!SMALL.contains(u16::MAX));
+    assert_eq!(WITH_ERROR.len(), 5);
+    assert_eq!(
+        (&WITH_ERROR).into_iter().collect::<Vec<_>>(),
+        [0, 1, 2, 3, u16::MAX]
+    );
+    assert_eq!(
+        WITH_ERROR.difference(SMALL).iter().collect::<Vec<_>>(),
+        [u16::MAX]
+    );
+    assert_eq!(WITH_ERROR.intersection(EVENS).len(), 2);
+    assert_eq!(WITH_ERROR.without(u16::MAX), SMALL);
+}
+
+#[test]
+#[should_panic(expected = "node kind ID out of range")]
+fn test_out_of_range() {
+    let _ = SMALL.with(128);
+}
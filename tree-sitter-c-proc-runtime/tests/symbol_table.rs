@@ -0,0 +1,35 @@
+// Tests for the perfect-hash layout of symbol tables, with tree-sitter-c
+use tree_sitter_c_proc_runtime::symbol_table::{self, Layout};
+
+#[test]
+fn test_layout() {
+    let language = tree_sitter_c::language();
+    let mut keys: Vec<(&str, bool)> = (0..language.node_kind_count() as u16)
+        .filter(|&id| language.node_kind_is_visible(id))
+        .map(|id| {
+            let name = language.node_kind_for_id(id).unwrap();
+            (name, language.node_kind_is_named(id))
+        })
+        .collect();
+    keys.sort();
+    keys.dedup();
+
+    // Every key gets a slot of its own
+    let layout = symbol_table::layout(&keys);
+    let mut slots = layout.order.clone();
+    slots.sort();
+    assert_eq!(slots, (0..keys.len()).collect::<Vec<_>>());
+
+    // The layout is deterministic
+    assert_eq!(symbol_table::layout(&keys), layout);
+
+    // Maps can be empty
+    assert_eq!(
+        symbol_table::layout(&[]),
+        Layout {
+            seed: 0,
+            displacements: vec![],
+            order: vec![],
+        }
+    );
+}
@@ -0,0 +1,175 @@
+// Tests for the tree iterators, with tree-sitter-c
+mod common;
+
+use common::parse;
+use tree_sitter::Node;
+use tree_sitter_c_proc_runtime::traversal::{
+    ancestors, children_by_field, descendants, descendants_of_kind, siblings, Order,
+};
+use tree_sitter_c_proc_runtime::KindSet;
+
+fn kinds<'a>(nodes: impl Iterator<Item = Node<'a>>) -> Vec<&'static str> {
+    nodes.map(|node| node.kind()).collect()
+}
+
+#[test]
+fn test_descendants_order() {
+    let tree = parse("int x = a + 1;");
+    let init_declarator = tree
+        .root_node()
+        .named_child(0)
+        .unwrap()
+        .child_by_field_name("declarator")
+        .unwrap();
+
+    assert_eq!(
+        kinds(descendants(init_declarator)),
+        [
+            "init_declarator",
+            "identifier",
+            "=",
+            "binary_expression",
+            "identifier",
+            "+",
+            "number_literal",
+        ]
+    );
+    assert_eq!(
+        kinds(descendants(init_declarator).post_order()),
+        [
+            "identifier",
+            "=",
+            "identifier",
+            "+",
+            "number_literal",
+            "binary_expression",
+            "init_declarator",
+        ]
+    );
+    assert_eq!(
+        kinds(descendants(init_declarator).order(Order::Post)),
+        kinds(descendants(init_declarator).post_order())
+    );
+
+    // The walk stays inside the node it started at
+    let identifier = init_declarator.named_child(0).unwrap();
+    assert_eq!(kinds(descendants(identifier)), ["identifier"]);
+    assert_eq!(kinds(descendants(identifier).post_order()), ["identifier"]);
+}
+
+#[test]
+fn test_descendants_of_kind() {
+    let language = tree_sitter_c::language();
+    let tree = parse("int f(void) { g(1); if (x) { h(2, 3); } return 4; }");
+    let root = tree.root_node();
+
+    let call_expression = language.id_for_node_kind("call_expression", true);
+    let number_literal = language.id_for_node_kind("number_literal", true);
+    let calls_and_numbers: KindSet<6> = KindSet::from_ids(&[call_expression, number_literal]);
+
+    // Nodes report the ID `id_for_node_kind` returns
+    assert_eq!(descendants_of_kind(root, call_expression).count(), 2);
+    assert_eq!(descendants_of_kind(root, number_literal).count(), 4);
+    assert_eq!(descendants_of_kind(root, calls_and_numbers).count(), 6);
+    assert_eq!(
+        descendants_of_kind(root, |id| id == number_literal)
+            .post_order()
+            .count(),
+        4
+    );
+
+    // Pruning still visits the pruned node, but not what it contains
+    let if_statement = language.id_for_node_kind("if_statement", true);
+    let numbers_outside_ifs = descendants_of_kind(root, number_literal)
+        .prune(|node| node.kind_id() == if_statement)
+        .count();
+    assert_eq!(numbers_outside_ifs, 2);
+    let pruned_post_order = descendants_of_kind(root, calls_and_numbers)
+        .prune(|node| node.kind_id() == call_expression)
+        .post_order()
+        .count();
+    assert_eq!(pruned_post_order, 3);
+
+    // Skipping the subtree of the last node, in pre-order
+    let mut calls = descendants_of_kind(root, calls_and_numbers);
+    let mut found = Vec::new();
+    while let Some(node) = calls.next() {
+        if node.kind_id() == call_expression {
+            calls.skip_subtree();
+        }
+        found.push(node.kind());
+    }
+    assert_eq!(
+        found,
+        ["call_expression", "call_expression", "number_literal"]
+    );
+}
+
+#[test]
+fn test_deep_tree() {
+    // Recursing once per level would overflow the stack of a test thread
+    let depth = 100_000;
+    let code = format!("int x = {}1{};", "(".repeat(depth), ")".repeat(depth));
+    let tree = parse(&code);
+    let root = tree.root_node();
+
+    let parenthesized_expression =
+        tree_sitter_c::language().id_for_node_kind("parenthesized_expression", true);
+    assert_eq!(
+        descendants_of_kind(root, parenthesized_expression).count(),
+        depth
+    );
+    assert_eq!(
+        descendants(root).post_order().count(),
+        descendants(root).count()
+    );
+}
+
+#[test]
+fn test_children_by_field() {
+    let language = tree_sitter_c::language();
+    let tree = parse("int a, *b, c[2];");
+    let declaration = tree.root_node().named_child(0).unwrap();
+    let declarator = language.field_id_for_name("declarator").unwrap();
+
+    assert_eq!(
+        kinds(children_by_field(declaration, declarator)),
+        ["identifier", "pointer_declarator", "array_declarator"]
+    );
+    let type_field = std::num::NonZeroU16::new(language.field_id_for_name("type").unwrap());
+    assert_eq!(
+        kinds(children_by_field(declaration, type_field.unwrap())),
+        ["primitive_type"]
+    );
+    let identifier = declaration.child(1).unwrap();
+    assert_eq!(identifier.kind(), "identifier");
+    assert_eq!(children_by_field(identifier, declarator).count(), 0);
+}
+
+#[test]
+fn test_ancestors_and_siblings() {
+    let tree = parse("int f(void) { return 1; }");
+    let root = tree.root_node();
+    let number = descendants(root)
+        .find(|node| node.kind() == "number_literal")
+        .unwrap();
+
+    assert_eq!(
+        kinds(ancestors(number)),
+        [
+            "return_statement",
+            "compound_statement",
+            "function_definition",
+            "translation_unit",
+        ]
+    );
+    assert_eq!(ancestors(root).count(), 0);
+
+    let function = root.named_child(0).unwrap();
+    let primitive_type = function.child(0).unwrap();
+    assert_eq!(
+        kinds(siblings(primitive_type)),
+        ["function_declarator", "compound_statement"]
+    );
+    assert_eq!(kinds(siblings(number)), [";"]);
+}
@@ -0,0 +1,132 @@
+//! Constants for every node kind, keyword and field of a grammar, written by a
+//! build script.
+//!
+//! Crates that avoid proc macros generate a `kinds.rs` in their `build.rs`:
+//!
+//! ```no_run
+//! // build.rs
+//! let out = std::path::Path::new(&std::env::var_os("OUT_DIR").unwrap()).join("kinds.rs");
+//! tree_sitter_proc_core::codegen::write_constants(&tree_sitter_c::language(), out).unwrap();
+//! ```
+//!
+//! and include it where the constants should live:
+//!
+//! ```ignore
+//! pub mod c {
+//!     include!(concat!(env!("OUT_DIR"), "/kinds.rs"));
+//! }
+//!
+//! match node.kind_id() {
+//!     c::IF_STATEMENT => {}
+//!     _ => {}
+//! }
+//! let condition = node.child_by_field_id(c::field::CONDITION.get());
+//! ```
+//!
+//! Named node kinds are constants at the top level of the file, anonymous
+//! tokens such as keywords and operators are in `kw`, and fields are in
+//! `field`. The names are `SCREAMING_SNAKE_CASE` spellings of the kinds with
+//! punctuation spelled out, so `"+="` becomes `kw::PLUS_EQ`. Each module is a
+//! namespace of its own, so the `null` kind and the `"NULL"` token are `NULL`
+//! and `kw::NULL`, while `node_kind_enum!` has to tell them apart as `Null` and
+//! `Null2`.
+
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use tree_sitter::Language;
+
+use crate::naming;
+use crate::symbols::{self, NodeKind};
+
+/// Returns the contents of a `kinds.rs` with constants for `language`.
+pub fn constants(language: &Language) -> String {
+    let (named, anonymous): (Vec<NodeKind>, Vec<NodeKind>) = symbols::node_kinds(language)
+        .into_iter()
+        .partition(|node_kind| node_kind.named);
+
+    let mut file = String::from(
+        "// Generated by tree_sitter_proc_core::codegen::write_constants, do not edit.\n",
+    );
+
+    write_node_kinds(&mut file, &named, "", "node kind");
+
+    file.push_str("\n/// The IDs of keywords and other anonymous tokens.\npub mod kw {\n");
+    write_node_kinds(&mut file, &anonymous, "    ", "token");
+    file.push_str("}\n");
+
+    file.push_str("\n/// The IDs of fields.\npub mod field {\n");
+    for id in symbols::field_ids(language) {
+        let Some(name) = language.field_name_for_id(id) else {
+            continue;
+        };
+        let ident = screaming_snake_case(&naming::upper_camel_case(name));
+        write!(
+            file,
+            "\n    /// The `{name}` field.\n    \
+             pub const {ident}: ::core::num::NonZeroU16 = \
+             match ::core::num::NonZeroU16::new({id}) {{\n        \
+             ::core::option::Option::Some(field_id) => field_id,\n        \
+             ::core::option::Option::None => ::core::unreachable!(),\n    \
+             }};\n"
+        )
+        .unwrap();
+    }
+    file.push_str("}\n");
+
+    file
+}
+
+/// Writes the constants for `language` to `path`, typically in `OUT_DIR`.
+///
+/// The file is left alone when its contents wouldn't change, so the crate that
+/// includes it isn't rebuilt needlessly.
+pub fn write_constants(language: &Language, path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    let contents = constants(language);
+    if fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+        return Ok(());
+    }
+    fs::write(path, contents)
+}
+
+/// Appends a `u16` constant for every node kind, each holding the ID
+/// `Node::kind_id` reports.
+///
+/// Names that belong to several symbol IDs, such as aliased rules, also get an
+/// `_IDS` slice with all of them, for IDs that come from the symbol table
+/// rather than from `Node::kind_id`.
+fn write_node_kinds(file: &mut String, node_kinds: &[NodeKind], indent: &str, category: &str) {
+    let idents = naming::node_kind_idents(node_kinds);
+
+    for (node_kind, ident) in node_kinds.iter().zip(&idents) {
+        let ident = screaming_snake_case(ident);
+        let name = node_kind.name.escape_debug();
+        let id = node_kind.id;
+
+        write!(
+            file,
+            "\n{indent}/// The `{name}` {category}.\n\
+             {indent}pub const {ident}: u16 = {id};\n"
+        )
+        .unwrap();
+
+        if node_kind.ids.len() > 1 {
+            let ids: Vec<String> = node_kind.ids.iter().map(u16::to_string).collect();
+            write!(
+                file,
+                "{indent}/// Every symbol ID of the `{name}` {category}.\n\
+                 {indent}pub const {ident}_IDS: &[u16] = &[{}];\n",
+                ids.join(", ")
+            )
+            .unwrap();
+        }
+    }
+}
+
+/// Converts an `UpperCamelCase` identifier to `SCREAMING_SNAKE_CASE`.
+fn screaming_snake_case(ident: &str) -> String {
+    naming::snake_case(ident).to_ascii_uppercase()
+}
@@ -0,0 +1,344 @@
+//! Error messages for names that are not part of a grammar.
+//!
+//! When a lookup misses, the macros enumerate the grammar's symbol and field
+//! tables, rank every candidate by edit distance and suggest the closest ones.
+
+use tree_sitter::{Language, QueryError, QueryErrorKind};
+
+use crate::node_types::NodeTypes;
+use crate::{symbols, LangName};
+
+/// Maximum number of suggestions listed in a single error message.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Builds the error message for a `kind!` lookup that found nothing.
+pub fn unknown_kind(language: &Language, name: LangName, requested: &str) -> String {
+    let mut message = format!(
+        "\"{requested}\" is not a valid node kind in the {} grammar",
+        name.crate_name()
+    );
+
+    if !symbols::ids_for_node_kind(language, requested, false).is_empty() {
+        message.push_str(&format!(
+            "; it exists as an anonymous token, use `{}` instead",
+            name.invocation("kw", requested)
+        ));
+    } else if name
+        .node_types()
+        .is_some_and(|node_types| node_types.is_supertype(requested))
+    {
+        message.push_str(&format!(
+            "; it is a supertype, use `{}` to match all of its subtypes",
+            name.invocation("supertype", requested)
+        ));
+    } else {
+        append_suggestions(&mut message, requested, symbol_names(language, true));
+    }
+
+    message
+}
+
+/// Builds the error message for a `kw!` lookup that found nothing.
+pub fn unknown_keyword(language: &Language, name: LangName, requested: &str) -> String {
+    let mut message = format!(
+        "\"{requested}\" is not a valid keyword in the {} grammar",
+        name.crate_name()
+    );
+
+    if !symbols::ids_for_node_kind(language, requested, true).is_empty() {
+        message.push_str(&format!(
+            "; it exists as a named node kind, use `{}` instead",
+            name.invocation("kind", requested)
+        ));
+    } else {
+        append_suggestions(&mut message, requested, symbol_names(language, false));
+    }
+
+    message
+}
+
+/// Builds the error message for a `field!` lookup that found nothing.
+pub fn unknown_field(language: &Language, name: LangName, requested: &str) -> String {
+    let mut message = format!(
+        "\"{requested}\" is not a valid field in the {} grammar",
+        name.crate_name()
+    );
+    append_suggestions(&mut message, requested, field_names(language));
+    message
+}
+
+/// Builds the error message for a `supertype!` lookup that found nothing.
+pub fn unknown_supertype(name: LangName, node_types: &NodeTypes, requested: &str) -> String {
+    let mut message = format!(
+        "\"{requested}\" is not a supertype in the {} grammar",
+        name.crate_name()
+    );
+    append_suggestions(&mut message, requested, node_types.supertype_names());
+    message
+}
+
+/// Builds the error message for a `field!` lookup whose node kind can't have
+/// the requested field.
+pub fn field_not_in_kind(name: LangName, kind: &str, requested: &str, fields: &[&str]) -> String {
+    let mut message = format!(
+        "\"{kind}\" nodes have no \"{requested}\" field in the {} grammar",
+        name.crate_name()
+    );
+
+    if fields.is_empty() {
+        message.push_str(&format!("; \"{kind}\" has no fields"));
+    } else {
+        let quoted: Vec<String> = fields.iter().map(|name| format!("\"{name}\"")).collect();
+        message.push_str(&format!("; its fields are {}", quoted.join(", ")));
+    }
+
+    message
+}
+
+/// Builds the error message for a `field_path!` step whose field none of the
+/// node kinds that can occur before it have.
+///
+/// `fields` are the fields those kinds do have, which are suggested when one is
+/// close to the requested name.
+pub fn field_not_in_kinds(
+    name: LangName,
+    kinds: &[&str],
+    requested: &str,
+    fields: &[&str],
+) -> String {
+    let mut message = format!(
+        "no node that can occur here has a \"{requested}\" field in the {} grammar; \
+         the nodes that can are {}",
+        name.crate_name(),
+        quoted_list(kinds)
+    );
+    append_suggestions(&mut message, requested, fields.to_vec());
+    message
+}
+
+/// Builds the error message for a `field_path!` kind filter that no node that
+/// can occur at its step matches.
+pub fn kind_not_reachable(name: LangName, requested: &str, kinds: &[&str]) -> String {
+    let mut message = format!(
+        "\"{requested}\" nodes can't occur here in the {} grammar",
+        name.crate_name()
+    );
+
+    if kinds.is_empty() {
+        message.push_str("; no named node can");
+    } else {
+        message.push_str(&format!("; the nodes that can are {}", quoted_list(kinds)));
+    }
+
+    message
+}
+
+/// Quotes the first few `names` for a message, counting the rest.
+fn quoted_list(names: &[&str]) -> String {
+    const MAX_LISTED: usize = 6;
+
+    let mut quoted: Vec<String> = names
+        .iter()
+        .take(MAX_LISTED)
+        .map(|name| format!("\"{name}\""))
+        .collect();
+    if names.len() > MAX_LISTED {
+        quoted.push(format!("and {} more", names.len() - MAX_LISTED));
+    }
+    quoted.join(", ")
+}
+
+/// Returns the names of all visible symbols with the given named-ness.
+fn symbol_names(language: &Language, named: bool) -> Vec<&'static str> {
+    symbols::visible_symbols(language, named)
+        .map(|(_, name)| name)
+        .collect()
+}
+
+/// Builds the error message for a check that needs the grammar's
+/// `node-types.json` when it wasn't given.
+pub fn missing_node_types(name: LangName, check: &str) -> String {
+    format!(
+        "`{check}` needs the node-types.json of the {} grammar, which this crate doesn't provide",
+        name.crate_name()
+    )
+}
+
+/// Builds the error message for a `query!` literal that `Query::new` rejected.
+pub fn query_error(
+    language: &Language,
+    name: LangName,
+    source: &str,
+    error: &QueryError,
+) -> String {
+    let requested = &error.message;
+    let mut message = format!(
+        "invalid query at line {}, column {}: ",
+        error.row + 1,
+        error.column + 1
+    );
+
+    match error.kind {
+        QueryErrorKind::NodeType => {
+            // Anonymous tokens are written as string literals in queries.
+            let named = !source[..error.offset.min(source.len())].ends_with('"');
+            message.push_str(&unknown_pattern_kind(language, name, requested, named));
+        }
+        QueryErrorKind::Field => message.push_str(&unknown_field(language, name, requested)),
+        QueryErrorKind::Capture => {
+            message.push_str(&format!("@{requested} is not a capture of this pattern"));
+        }
+        QueryErrorKind::Predicate => {
+            message.push_str(&format!("invalid predicate, {requested}"));
+        }
+        QueryErrorKind::Structure => {
+            message.push_str(&format!(
+                "this pattern can never match a {} syntax tree",
+                name.crate_name()
+            ));
+        }
+        QueryErrorKind::Syntax => message.push_str("invalid syntax"),
+        QueryErrorKind::Language => {
+            message.push_str(&format!(
+                "the {} grammar is incompatible with this tree-sitter version",
+                name.crate_name()
+            ));
+        }
+    }
+
+    message
+}
+
+/// Builds the error message for a node kind in a query-like pattern that
+/// found nothing, where named kinds are written `(kind)` and anonymous tokens
+/// `"token"`.
+pub fn unknown_pattern_kind(
+    language: &Language,
+    name: LangName,
+    requested: &str,
+    named: bool,
+) -> String {
+    let mut message = if named {
+        format!(
+            "\"{requested}\" is not a valid node kind in the {} grammar",
+            name.crate_name()
+        )
+    } else {
+        format!(
+            "\"{requested}\" is not a valid anonymous token in the {} grammar",
+            name.crate_name()
+        )
+    };
+
+    if !symbols::ids_for_node_kind(language, requested, !named).is_empty() {
+        if named {
+            message.push_str(&format!(
+                "; it exists as an anonymous token, write it as \"{requested}\" instead"
+            ));
+        } else {
+            message.push_str(&format!(
+                "; it exists as a named node kind, write it as ({requested}) instead"
+            ));
+        }
+    } else {
+        append_suggestions(&mut message, requested, symbol_names(language, named));
+    }
+
+    message
+}
+
+/// Builds the error message for a `CQuery` struct field that matches no capture
+/// of its query.
+pub fn unknown_capture(requested: &str, captures: &[&str]) -> String {
+    let mut message = format!("`{requested}` does not match any capture of the query");
+
+    if captures.is_empty() {
+        message.push_str("; the query has no captures");
+    } else {
+        let suggestions = closest_matches(requested, captures.to_vec());
+        if suggestions.is_empty() {
+            let listed: Vec<String> = captures.iter().map(|name| format!("@{name}")).collect();
+            message.push_str(&format!("; its captures are {}", listed.join(", ")));
+        } else {
+            append_suggestions(&mut message, requested, suggestions);
+        }
+    }
+
+    message
+}
+
+/// Returns the names of all fields in the grammar.
+fn field_names(language: &Language) -> Vec<&'static str> {
+    symbols::field_ids(language)
+        .filter_map(|id| language.field_name_for_id(id))
+        .collect()
+}
+
+/// Appends a "did you mean" clause listing the candidates closest to `requested`.
+fn append_suggestions(message: &mut String, requested: &str, candidates: Vec<&str>) {
+    let suggestions = closest_matches(requested, candidates);
+
+    match suggestions.as_slice() {
+        [] => {}
+        [only] => message.push_str(&format!("; did you mean \"{only}\"?")),
+        many => {
+            let quoted: Vec<String> = many.iter().map(|name| format!("\"{name}\"")).collect();
+            message.push_str(&format!("; did you mean one of {}?", quoted.join(", ")));
+        }
+    }
+}
+
+/// Ranks `candidates` by edit distance to `requested` and returns the best few.
+///
+/// Candidates further away than a third of the requested name's length are
+/// dropped, so unrelated names are never suggested.
+fn closest_matches<'a>(requested: &str, candidates: Vec<&'a str>) -> Vec<&'a str> {
+    let max_distance = requested.chars().count().max(3) / 3;
+
+    let mut ranked: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(requested, candidate), candidate))
+        .filter(|&(distance, _)| distance <= max_distance)
+        .collect();
+
+    ranked.sort();
+    ranked.dedup_by(|a, b| a.1 == b.1);
+    ranked
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Computes the edit distance between two strings.
+///
+/// This is the optimal string alignment distance: insertions, deletions,
+/// substitutions and transpositions of adjacent characters each cost one, so
+/// typos such as `"whlie"` stay close to `"while"`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut table = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in table.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in table[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (table[i - 1][j] + 1)
+                .min(table[i][j - 1] + 1)
+                .min(table[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(table[i - 2][j - 2] + 1);
+            }
+            table[i][j] = distance;
+        }
+    }
+
+    table[a.len()][b.len()]
+}
@@ -0,0 +1,428 @@
+//! The expansion of the `kind!`, `kinds!`, `kw!`, `kws!`, `supertype!`,
+//! `field!` and `field_id!` macros, shared by the proc macro crates of every grammar.
+//!
+//! A proc macro crate can't export anything but macros, so the lookups and
+//! error messages live in this ordinary library instead. A per-language crate
+//! only forwards its input together with the grammar's `Language`:
+//!
+//! ```ignore
+//! use proc_macro::TokenStream;
+//! use tree_sitter_proc_core::LangName;
+//!
+//! const GRAMMAR: LangName = LangName::new("tree-sitter-c").with_node_types(tree_sitter_c::NODE_TYPES);
+//!
+//! #[proc_macro]
+//! pub fn kind(input: TokenStream) -> TokenStream {
+//!     tree_sitter_proc_core::expand_kind(&tree_sitter_c::language(), input.into(), GRAMMAR).into()
+//! }
+//! ```
+//!
+//! Unless the ID types of a runtime crate are selected with
+//! [`LangName::with_typed_ids`], the expansions only refer to `::core`, so they
+//! work in `no_std` crates and when `std` is shadowed.
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::punctuated::Punctuated;
+use syn::{LitStr, Token};
+use tree_sitter::Language;
+
+pub mod codegen;
+pub mod diagnostics;
+pub mod naming;
+pub mod node_types;
+pub mod symbols;
+
+use node_types::NodeTypes;
+
+/// How the macros of a grammar refer to it.
+///
+/// Error messages name the grammar's crate, and suggest invocations that
+/// select the grammar with `argument` when the macros take one. The grammar's
+/// `node-types.json` is only parsed by the checks that need the structure of
+/// the grammar, such as `supertype!` and `field!` with a node kind.
+#[derive(Clone, Copy, Debug)]
+pub struct LangName<'a> {
+    crate_name: &'a str,
+    argument: Option<&'a str>,
+    node_types: Option<&'a str>,
+    typed_ids: Option<&'a str>,
+}
+
+impl<'a> LangName<'a> {
+    /// Names the grammar by its crate, such as `tree-sitter-c`.
+    pub const fn new(crate_name: &'a str) -> Self {
+        LangName {
+            crate_name,
+            argument: None,
+            node_types: None,
+            typed_ids: None,
+        }
+    }
+
+    /// Sets the first macro argument that selects the grammar, such as `cpp` in
+    /// `kind!(cpp, "class_specifier")`.
+    pub const fn with_argument(self, argument: &'a str) -> Self {
+        LangName {
+            argument: Some(argument),
+            ..self
+        }
+    }
+
+    /// Sets the contents of the grammar's `node-types.json`, which must be
+    /// valid.
+    pub const fn with_node_types(self, node_types: &'a str) -> Self {
+        LangName {
+            node_types: Some(node_types),
+            ..self
+        }
+    }
+
+    /// Makes the macros expand to the `KindId`, `TokenId` and `FieldId` types
+    /// of the crate at `runtime`, such as `::tree_sitter_c_proc_runtime`,
+    /// instead of bare integers.
+    ///
+    /// `field_id!` keeps expanding to a `u16` literal, since it exists for
+    /// patterns on raw field IDs.
+    pub const fn with_typed_ids(self, runtime: &'a str) -> Self {
+        LangName {
+            typed_ids: Some(runtime),
+            ..self
+        }
+    }
+
+    /// Returns the name of the grammar's crate.
+    pub fn crate_name(&self) -> &'a str {
+        self.crate_name
+    }
+
+    /// Parses the grammar's `node-types.json`, if it was given.
+    pub fn node_types(&self) -> Option<NodeTypes> {
+        self.node_types.map(NodeTypes::parse)
+    }
+
+    /// Returns the path of the crate with the typed IDs, if the macros expand
+    /// to them.
+    fn typed_ids(&self) -> Option<TokenStream> {
+        let runtime = self.typed_ids?;
+        Some(runtime.parse().expect("the runtime crate is a valid path"))
+    }
+
+    /// Spells out a macro invocation for `argument`, selecting the grammar if
+    /// the macros take it as an argument.
+    pub fn invocation(&self, macro_name: &str, argument: &str) -> String {
+        match self.argument {
+            Some(grammar) => format!("{macro_name}!({grammar}, \"{argument}\")"),
+            None => format!("{macro_name}!(\"{argument}\")"),
+        }
+    }
+}
+
+/// Expands `kind!("name")` to the ID of a named node kind.
+///
+/// Names that belong to several symbol IDs expand to the one `Node::kind_id`
+/// reports, so the expansion is a value; `kinds!` matches all of them.
+pub fn expand_kind(language: &Language, input: TokenStream, name: LangName) -> TokenStream {
+    expand_symbol(language, input, true, name)
+}
+
+/// Expands `kinds!("a", "b")` to an or-pattern of every ID of several node
+/// kinds.
+pub fn expand_kinds(language: &Language, input: TokenStream, name: LangName) -> TokenStream {
+    expand_symbols(language, input, true, name)
+}
+
+/// Expands `kw!("name")` to the ID of a keyword or other anonymous token.
+///
+/// Like `kind!`, keywords that belong to several symbol IDs expand to the one
+/// `Node::kind_id` reports; `kws!` matches all of them.
+pub fn expand_kw(language: &Language, input: TokenStream, name: LangName) -> TokenStream {
+    expand_symbol(language, input, false, name)
+}
+
+/// Expands `kws!("a", "b")` to an or-pattern of every ID of several keywords
+/// or other anonymous tokens.
+pub fn expand_kws(language: &Language, input: TokenStream, name: LangName) -> TokenStream {
+    expand_symbols(language, input, false, name)
+}
+
+/// Expands `supertype!("_name")` to an or-pattern of the IDs of every concrete
+/// subtype, expanding nested supertypes recursively.
+///
+/// Needs the grammar's `node-types.json`.
+pub fn expand_supertype(language: &Language, input: TokenStream, name: LangName) -> TokenStream {
+    let string_literal = match parse_symbol.parse2(input) {
+        Ok(string_literal) => string_literal,
+        Err(error) => return error.to_compile_error(),
+    };
+
+    // Get the string value
+    let requested_supertype = string_literal.value();
+
+    let Some(node_types) = name.node_types() else {
+        let message = diagnostics::missing_node_types(name, "supertype!");
+        return quote_spanned!(
+            string_literal.span() =>
+            compile_error!(#message)
+        );
+    };
+
+    if let Some(subtypes) = node_types.concrete_subtypes(&requested_supertype) {
+        // Subtypes are matched against `Node::kind_id`, so they are node kinds
+        // even when anonymous
+        let found_ids = subtypes.iter().flat_map(|subtype| {
+            symbols::ids_for_node_kind(language, &subtype.kind, subtype.named)
+                .into_iter()
+                .map(|found_id| symbol_id(found_id, true, name))
+        });
+
+        // The leading `|` keeps the pattern from silently turning into a
+        // bitwise-or expression when used outside of a pattern.
+        quote! {
+            #(| #found_ids)*
+        }
+    } else {
+        let message = diagnostics::unknown_supertype(name, &node_types, &requested_supertype);
+        quote_spanned!(
+            string_literal.span() =>
+            compile_error!(#message)
+        )
+    }
+}
+
+/// Expands `field!("name")` or `field!("kind", "name")` to the ID of a field as
+/// a `core::num::NonZeroU16` constant expression.
+///
+/// Checking the field against a node kind needs the grammar's
+/// `node-types.json`.
+pub fn expand_field(language: &Language, input: TokenStream, name: LangName) -> TokenStream {
+    match lookup_field_id(language, input, name) {
+        // `Option::unwrap` isn't usable in constants on every supported
+        // compiler, so unwrap with a `match` instead.
+        Ok(id_number) => {
+            let field_id = quote! {
+                match ::core::num::NonZeroU16::new(#id_number) {
+                    ::core::option::Option::Some(field_id) => field_id,
+                    ::core::option::Option::None => ::core::unreachable!(),
+                }
+            };
+            match name.typed_ids() {
+                Some(runtime) => quote!(#runtime::FieldId::new(#field_id)),
+                None => field_id,
+            }
+        }
+        Err(error) => error,
+    }
+}
+
+/// Expands `field_id!("name")` or `field_id!("kind", "name")` to the ID of a
+/// field as a `u16` literal, which is usable in patterns.
+pub fn expand_field_id(language: &Language, input: TokenStream, name: LangName) -> TokenStream {
+    match lookup_field_id(language, input, name) {
+        Ok(id_number) => quote! {
+            #id_number
+        },
+        Err(error) => error,
+    }
+}
+
+/// Expands a single node kind (`named`) or keyword literal to the ID nodes of
+/// that kind report.
+fn expand_symbol(
+    language: &Language,
+    input: TokenStream,
+    named: bool,
+    name: LangName,
+) -> TokenStream {
+    let string_literal = match parse_symbol.parse2(input) {
+        Ok(string_literal) => string_literal,
+        Err(error) => return error.to_compile_error(),
+    };
+
+    let found_ids = match lookup_symbol_ids(language, &string_literal, named, name) {
+        Ok(found_ids) => found_ids,
+        Err(error) => return error,
+    };
+
+    // Aliases share the name of the symbol they stand for, and tree-sitter
+    // reports nodes of every one of them with the ID of that symbol, which is
+    // the one the lookup by name returns.
+    let found_id = match found_ids.as_slice() {
+        [found_id] => *found_id,
+        _ => language.id_for_node_kind(&string_literal.value(), named),
+    };
+    symbol_id(found_id, named, name)
+}
+
+/// Expands a list of node kind (`named`) or keyword literals to an or-pattern of
+/// all of their IDs.
+fn expand_symbols(
+    language: &Language,
+    input: TokenStream,
+    named: bool,
+    name: LangName,
+) -> TokenStream {
+    let string_literals = match Punctuated::<LitStr, Token![,]>::parse_terminated.parse2(input) {
+        Ok(string_literals) => string_literals,
+        Err(error) => return error.to_compile_error(),
+    };
+
+    if string_literals.is_empty() {
+        return if named {
+            quote!(compile_error!("Expected at least one node kind"))
+        } else {
+            quote!(compile_error!("Expected at least one keyword"))
+        };
+    }
+
+    // Unknown names become `compile_error!` alternatives of the same pattern,
+    // so every bad name is reported with its own span.
+    let alternatives = string_literals.iter().flat_map(|string_literal| {
+        match lookup_symbol_ids(language, string_literal, named, name) {
+            Ok(found_ids) => found_ids
+                .iter()
+                .map(|&found_id| symbol_id(found_id, named, name))
+                .collect(),
+            Err(error) => vec![error],
+        }
+    });
+
+    // The leading `|` keeps the pattern from silently turning into a
+    // bitwise-or expression when used outside of a pattern.
+    quote! {
+        #(| #alternatives)*
+    }
+}
+
+/// Parses a string literal with an optional trailing comma.
+fn parse_symbol(input: ParseStream) -> syn::Result<LitStr> {
+    let string_literal = input.parse()?;
+    input.parse::<Option<Token![,]>>()?;
+    Ok(string_literal)
+}
+
+/// Looks up every symbol ID for a node kind (`named`) or keyword literal.
+///
+/// Returns a spanned `compile_error!` if the name is unknown.
+fn lookup_symbol_ids(
+    language: &Language,
+    string_literal: &LitStr,
+    named: bool,
+    name: LangName,
+) -> Result<Vec<u16>, TokenStream> {
+    // Get the string value
+    let requested = string_literal.value();
+
+    // `ERROR` isn't part of the symbol table, but nodes report its ID
+    if named && requested == "ERROR" {
+        return Ok(vec![u16::MAX]);
+    }
+
+    let found_ids = symbols::ids_for_node_kind(language, &requested, named);
+
+    let message = if found_ids.is_empty() && named {
+        diagnostics::unknown_kind(language, name, &requested)
+    } else if found_ids.is_empty() {
+        diagnostics::unknown_keyword(language, name, &requested)
+    } else {
+        return Ok(found_ids);
+    };
+
+    Err(quote_spanned!(
+        string_literal.span() =>
+        compile_error!(#message)
+    ))
+}
+
+/// Expands a symbol ID, which is also a pattern: a `u16` literal, or a
+/// `KindId` or `TokenId` with typed IDs.
+fn symbol_id(found_id: u16, named: bool, name: LangName) -> TokenStream {
+    match name.typed_ids() {
+        Some(runtime) if named => quote!(#runtime::KindId(#found_id)),
+        Some(runtime) => quote!(#runtime::TokenId(#found_id)),
+        None => quote!(#found_id),
+    }
+}
+
+/// Input of `field!` and `field_id!`: a field name, optionally preceded by the
+/// node kind it must belong to.
+struct FieldInput {
+    kind: Option<LitStr>,
+    field: LitStr,
+}
+
+impl Parse for FieldInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let first: LitStr = input.parse()?;
+        if input.is_empty() {
+            return Ok(FieldInput {
+                kind: None,
+                field: first,
+            });
+        }
+
+        input.parse::<Token![,]>()?;
+        let field = input.parse()?;
+        input.parse::<Option<Token![,]>>()?;
+
+        Ok(FieldInput {
+            kind: Some(first),
+            field,
+        })
+    }
+}
+
+/// Looks up the field ID for a field name literal.
+///
+/// Returns a spanned `compile_error!` if the field is unknown, or if the node
+/// kind given with it can't have that field.
+fn lookup_field_id(
+    language: &Language,
+    input: TokenStream,
+    name: LangName,
+) -> Result<u16, TokenStream> {
+    let field_input: FieldInput = syn::parse2(input).map_err(|error| error.to_compile_error())?;
+
+    // Get the string value
+    let requested_field = field_input.field.value();
+
+    let Some(found_id) = language.field_id_for_name(&requested_field) else {
+        let message = diagnostics::unknown_field(language, name, &requested_field);
+        return Err(quote_spanned!(
+            field_input.field.span() =>
+            compile_error!(#message)
+        ));
+    };
+
+    if let Some(kind_literal) = &field_input.kind {
+        let requested_kind = kind_literal.value();
+
+        let Some(node_types) = name.node_types() else {
+            let message = diagnostics::missing_node_types(name, "field!(kind, field)");
+            return Err(quote_spanned!(
+                kind_literal.span() =>
+                compile_error!(#message)
+            ));
+        };
+
+        let Some(fields) = node_types.field_names(&requested_kind) else {
+            let message = diagnostics::unknown_kind(language, name, &requested_kind);
+            return Err(quote_spanned!(
+                kind_literal.span() =>
+                compile_error!(#message)
+            ));
+        };
+
+        if !fields.contains(&requested_field.as_str()) {
+            let message =
+                diagnostics::field_not_in_kind(name, &requested_kind, &requested_field, &fields);
+            return Err(quote_spanned!(
+                field_input.field.span() =>
+                compile_error!(#message)
+            ));
+        }
+    }
+
+    Ok(found_id)
+}
@@ -0,0 +1,160 @@
+//! Rust identifiers for grammar names.
+//!
+//! Generated enums and types need an identifier for every node kind, including
+//! anonymous tokens such as `"("` or `"+="` that are not valid identifiers.
+
+use proc_macro2::{Ident, Span};
+
+use crate::symbols::NodeKind;
+
+/// Returns the `UpperCamelCase` spelling of a grammar name.
+///
+/// Underscores separate words and punctuation is spelled out, so
+/// `"function_definition"` becomes `FunctionDefinition` and `"+="` becomes
+/// `PlusEq`. Upper-case names are folded, so `"NULL"` becomes `Null`.
+pub fn upper_camel_case(name: &str) -> String {
+    let mut ident = String::new();
+    let mut start_of_word = true;
+
+    for character in name.chars() {
+        if character == '_' {
+            start_of_word = true;
+        } else if character.is_ascii_alphanumeric() {
+            if start_of_word {
+                ident.push(character.to_ascii_uppercase());
+            } else {
+                ident.push(character.to_ascii_lowercase());
+            }
+            start_of_word = false;
+        } else {
+            ident.push_str(&punctuation_name(character));
+            start_of_word = true;
+        }
+    }
+
+    if ident.is_empty() {
+        ident.push_str("Underscore");
+    }
+    if ident.starts_with(|character: char| character.is_ascii_digit()) {
+        ident.insert(0, 'N');
+    }
+    if ident == "Self" {
+        ident.push('_');
+    }
+
+    ident
+}
+
+/// Returns a unique `UpperCamelCase` identifier for every node kind.
+///
+/// Named kinds are assigned first so they always keep their plain spelling.
+/// When two kinds map to the same identifier, such as `"NULL"` and `null` or
+/// `"_alignof"` and `"alignof"`, later ones get a numeric suffix, the way the
+/// tree-sitter generator names its symbols.
+pub fn node_kind_idents(node_kinds: &[NodeKind]) -> Vec<String> {
+    let mut idents = vec![String::new(); node_kinds.len()];
+    let mut taken: Vec<String> = Vec::new();
+
+    let named_first = (0..node_kinds.len())
+        .filter(|&index| node_kinds[index].named)
+        .chain((0..node_kinds.len()).filter(|&index| !node_kinds[index].named));
+
+    for index in named_first {
+        let base = upper_camel_case(node_kinds[index].name);
+        let mut ident = base.clone();
+        let mut suffix = 2;
+        while taken.contains(&ident) {
+            ident = format!("{base}{suffix}");
+            suffix += 1;
+        }
+        taken.push(ident.clone());
+        idents[index] = ident;
+    }
+
+    idents
+}
+
+/// Converts an `UpperCamelCase` identifier back to `snake_case`.
+///
+/// Used on the output of [`node_kind_idents`] so that method names derived from
+/// node kinds are unique and valid even for kinds like `ERROR`.
+pub fn snake_case(ident: &str) -> String {
+    let mut snake = String::with_capacity(ident.len() + 4);
+    for (index, character) in ident.chars().enumerate() {
+        if character.is_ascii_uppercase() {
+            if index > 0 {
+                snake.push('_');
+            }
+            snake.push(character.to_ascii_lowercase());
+        } else {
+            snake.push(character);
+        }
+    }
+    snake
+}
+
+/// Spells out a punctuation character as an identifier fragment.
+fn punctuation_name(character: char) -> String {
+    let name = match character {
+        '~' => "Tilde",
+        '`' => "Backtick",
+        '!' => "Bang",
+        '@' => "At",
+        '#' => "Pound",
+        '$' => "Dollar",
+        '%' => "Percent",
+        '^' => "Caret",
+        '&' => "Amp",
+        '*' => "Star",
+        '(' => "LParen",
+        ')' => "RParen",
+        '-' => "Dash",
+        '+' => "Plus",
+        '=' => "Eq",
+        '{' => "LBrace",
+        '}' => "RBrace",
+        '[' => "LBrack",
+        ']' => "RBrack",
+        '\\' => "Backslash",
+        '|' => "Pipe",
+        ';' => "Semi",
+        ':' => "Colon",
+        '\'' => "SQuote",
+        '"' => "DQuote",
+        '<' => "Lt",
+        '>' => "Gt",
+        ',' => "Comma",
+        '.' => "Dot",
+        '?' => "QMark",
+        '/' => "Slash",
+        '\n' => "Lf",
+        '\r' => "Cr",
+        '\t' => "Tab",
+        ' ' => "Space",
+        other => return format!("U{:04X}", u32::from(other)),
+    };
+    name.to_string()
+}
+
+/// Returns an identifier for a `snake_case` grammar name such as a field.
+///
+/// Names that are Rust keywords become raw identifiers (`type` becomes
+/// `r#type`), except for those that can't be raw, which get a trailing
+/// underscore.
+pub fn snake_case_ident(name: &str) -> Ident {
+    const KEYWORDS: &[&str] = &[
+        "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do",
+        "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in",
+        "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+        "return", "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe",
+        "unsized", "use", "virtual", "where", "while", "yield",
+    ];
+
+    match name {
+        "self" | "super" | "crate" | "Self" | "_" => {
+            Ident::new(&format!("{name}_"), Span::call_site())
+        }
+        _ if KEYWORDS.contains(&name) => Ident::new_raw(name, Span::call_site()),
+        _ => Ident::new(name, Span::call_site()),
+    }
+}
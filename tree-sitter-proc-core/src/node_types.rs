@@ -0,0 +1,139 @@
+//! Static node type information read from the grammar's `node-types.json`.
+//!
+//! The symbol table exposed by `tree_sitter::Language` knows nothing about the
+//! structure of the grammar, so the macros that need supertypes, the fields of
+//! a node kind or what those fields can hold read the `node-types.json` bundled
+//! with the grammar's crate at compile time.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+/// One entry of `node-types.json`.
+#[derive(Deserialize)]
+pub struct NodeType {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub named: bool,
+    #[serde(default)]
+    pub fields: BTreeMap<String, FieldInfo>,
+    /// The named children that aren't in a field.
+    #[serde(default)]
+    pub children: Option<FieldInfo>,
+    #[serde(default)]
+    pub subtypes: Vec<NodeTypeRef>,
+}
+
+/// What a field of a node type can hold.
+#[derive(Deserialize)]
+pub struct FieldInfo {
+    pub multiple: bool,
+    pub required: bool,
+    pub types: Vec<NodeTypeRef>,
+}
+
+/// A reference to a node kind, as used in subtype and field type lists.
+#[derive(Clone, Deserialize, PartialEq, Eq)]
+pub struct NodeTypeRef {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub named: bool,
+}
+
+/// The parsed contents of `node-types.json`.
+pub struct NodeTypes {
+    node_types: Vec<NodeType>,
+}
+
+impl NodeTypes {
+    /// Parses the contents of a `node-types.json` file bundled with a grammar's
+    /// crate, or validated by `try_parse` before.
+    pub fn parse(json: &str) -> Self {
+        Self::try_parse(json).expect("node-types.json was validated when it was loaded")
+    }
+
+    /// Parses the contents of a `node-types.json` file that may be invalid.
+    pub fn try_parse(json: &str) -> serde_json::Result<Self> {
+        let node_types = serde_json::from_str(json)?;
+        Ok(NodeTypes { node_types })
+    }
+
+    /// Returns every entry of `node-types.json`, in file order.
+    pub fn node_types(&self) -> &[NodeType] {
+        &self.node_types
+    }
+
+    /// Returns the entry of the node kind `name` with the given named-ness.
+    pub fn node_type(&self, name: &str, named: bool) -> Option<&NodeType> {
+        self.node_types
+            .iter()
+            .find(|node_type| node_type.named == named && node_type.kind == name)
+    }
+
+    /// Returns the names of all supertypes in the grammar.
+    pub fn supertype_names(&self) -> Vec<&str> {
+        self.node_types
+            .iter()
+            .filter(|node_type| !node_type.subtypes.is_empty())
+            .map(|node_type| node_type.kind.as_str())
+            .collect()
+    }
+
+    /// Returns `true` if `name` is a supertype in the grammar.
+    pub fn is_supertype(&self, name: &str) -> bool {
+        self.supertype(name).is_some()
+    }
+
+    /// Returns the names of the fields a node of kind `name` can have, in
+    /// alphabetical order.
+    ///
+    /// Returns `None` if `name` is not a named node kind in the grammar.
+    pub fn field_names(&self, name: &str) -> Option<Vec<&str>> {
+        self.node_types
+            .iter()
+            .find(|node_type| node_type.named && node_type.kind == name)
+            .map(|node_type| node_type.fields.keys().map(String::as_str).collect())
+    }
+
+    /// Returns every concrete node kind below the supertype `name`.
+    ///
+    /// Subtypes that are themselves supertypes are expanded recursively. Returns
+    /// `None` if `name` is not a supertype.
+    pub fn concrete_subtypes(&self, name: &str) -> Option<Vec<NodeTypeRef>> {
+        let supertype = self.supertype(name)?;
+        let mut visited = vec![supertype.kind.as_str()];
+        let mut concrete = Vec::new();
+        self.collect_subtypes(supertype, &mut visited, &mut concrete);
+        Some(concrete)
+    }
+
+    fn collect_subtypes<'a>(
+        &'a self,
+        supertype: &'a NodeType,
+        visited: &mut Vec<&'a str>,
+        concrete: &mut Vec<NodeTypeRef>,
+    ) {
+        for subtype in &supertype.subtypes {
+            match self.supertype(&subtype.kind) {
+                Some(nested) if subtype.named => {
+                    if !visited.contains(&nested.kind.as_str()) {
+                        visited.push(&nested.kind);
+                        self.collect_subtypes(nested, visited, concrete);
+                    }
+                }
+                _ => {
+                    if !concrete.contains(subtype) {
+                        concrete.push(subtype.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    fn supertype(&self, name: &str) -> Option<&NodeType> {
+        self.node_types
+            .iter()
+            .find(|node_type| node_type.named && node_type.kind == name)
+            .filter(|node_type| !node_type.subtypes.is_empty())
+    }
+}
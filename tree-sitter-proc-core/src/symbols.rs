@@ -0,0 +1,89 @@
+//! Lookups in a grammar's symbol table.
+//!
+//! `Language::id_for_node_kind` stops at the first matching symbol, but the
+//! same visible name can belong to several symbol IDs, for example when a rule
+//! is aliased or a token is reused in another context. These helpers scan the
+//! whole table instead.
+
+use tree_sitter::Language;
+
+/// Returns the IDs and names of all visible symbols with the given named-ness.
+pub fn visible_symbols(
+    language: &Language,
+    named: bool,
+) -> impl Iterator<Item = (u16, &'static str)> + '_ {
+    (0..language.node_kind_count() as u16)
+        .filter(move |&id| {
+            language.node_kind_is_visible(id) && language.node_kind_is_named(id) == named
+        })
+        .filter_map(|id| Some((id, language.node_kind_for_id(id)?)))
+}
+
+/// Returns every visible symbol ID whose name and named-ness match, in ID order.
+///
+/// `Node::kind_id` reports the one `Language::id_for_node_kind` returns, which
+/// isn't necessarily the first.
+pub fn ids_for_node_kind(language: &Language, name: &str, named: bool) -> Vec<u16> {
+    visible_symbols(language, named)
+        .filter(|&(_, symbol_name)| symbol_name == name)
+        .map(|(id, _)| id)
+        .collect()
+}
+
+/// Returns every field ID of the grammar.
+///
+/// Field IDs start at 1, since 0 means "no field", so they are not indices
+/// into a list of the fields.
+pub fn field_ids(language: &Language) -> impl Iterator<Item = u16> {
+    1..=language.field_count() as u16
+}
+
+/// A visible node kind together with every symbol ID that carries its name.
+pub struct NodeKind {
+    pub name: &'static str,
+    pub named: bool,
+    /// The ID `Node::kind_id` reports for nodes of the kind.
+    pub id: u16,
+    pub ids: Vec<u16>,
+}
+
+/// Returns every distinct visible node kind, named and anonymous, ordered by
+/// the first symbol ID of each kind.
+///
+/// The `ERROR` kind that tree-sitter reports for unparseable input is not part
+/// of the symbol table, so it is appended at the end with its ID `u16::MAX`.
+pub fn node_kinds(language: &Language) -> Vec<NodeKind> {
+    let mut node_kinds: Vec<NodeKind> = Vec::new();
+
+    for id in 0..language.node_kind_count() as u16 {
+        if !language.node_kind_is_visible(id) {
+            continue;
+        }
+        let Some(name) = language.node_kind_for_id(id) else {
+            continue;
+        };
+        let named = language.node_kind_is_named(id);
+
+        match node_kinds
+            .iter_mut()
+            .find(|node_kind| node_kind.name == name && node_kind.named == named)
+        {
+            Some(node_kind) => node_kind.ids.push(id),
+            None => node_kinds.push(NodeKind {
+                name,
+                named,
+                id: language.id_for_node_kind(name, named),
+                ids: vec![id],
+            }),
+        }
+    }
+
+    node_kinds.push(NodeKind {
+        name: "ERROR",
+        named: true,
+        id: u16::MAX,
+        ids: vec![u16::MAX],
+    });
+
+    node_kinds
+}
@@ -0,0 +1,115 @@
+// Tests for the constants written for build scripts, against the tree-sitter-c grammar
+use std::collections::HashMap;
+
+use syn::{Expr, Item, ItemConst, Lit};
+use tree_sitter_proc_core::codegen;
+
+/// Returns the value of every `u16` constant in `items`, by name.
+fn u16_constants(items: &[Item]) -> HashMap<String, u16> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Const(ItemConst { ident, expr, .. }) => match &**expr {
+                Expr::Lit(literal) => match &literal.lit {
+                    Lit::Int(int) => Some((ident.to_string(), int.base10_parse().unwrap())),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the items of the module `name` in `items`.
+fn module<'a>(items: &'a [Item], name: &str) -> &'a [Item] {
+    items
+        .iter()
+        .find_map(|item| match item {
+            Item::Mod(module) if module.ident == name => Some(&module.content.as_ref()?.1[..]),
+            _ => None,
+        })
+        .unwrap_or_else(|| panic!("no `{name}` module"))
+}
+
+#[test]
+fn test_constants() {
+    let language = tree_sitter_c::language();
+    let file = syn::parse_file(&codegen::constants(&language)).unwrap();
+
+    // Named node kinds at the top level
+    let kinds = u16_constants(&file.items);
+    assert_eq!(
+        kinds["FUNCTION_DEFINITION"],
+        language.id_for_node_kind("function_definition", true)
+    );
+    assert_eq!(
+        kinds["TRANSLATION_UNIT"],
+        language.id_for_node_kind("translation_unit", true)
+    );
+    assert_eq!(kinds["ERROR"], u16::MAX);
+    assert_eq!(kinds["NULL"], language.id_for_node_kind("null", true));
+
+    // Anonymous tokens in `kw`, including punctuation
+    let keywords = u16_constants(module(&file.items, "kw"));
+    assert_eq!(keywords["IF"], language.id_for_node_kind("if", false));
+    assert_eq!(keywords["PLUS_EQ"], language.id_for_node_kind("+=", false));
+    // Names are only suffixed when they clash within a module
+    assert_eq!(keywords["NULL"], language.id_for_node_kind("NULL", false));
+    assert!(!keywords.contains_key("NULL2"));
+
+    // Fields in `field`, one per field of the grammar
+    let fields = module(&file.items, "field");
+    assert_eq!(fields.len(), language.field_count());
+    let declarator = fields
+        .iter()
+        .find_map(|item| match item {
+            Item::Const(constant) if constant.ident == "DECLARATOR" => Some(constant),
+            _ => None,
+        })
+        .unwrap();
+    let declarator_id = language.field_id_for_name("declarator").unwrap();
+    let expr = &declarator.expr;
+    assert!(quote::quote!(#expr)
+        .to_string()
+        .contains(&format!("NonZeroU16 :: new ({declarator_id})")));
+}
+
+#[test]
+fn test_aliased_kinds() {
+    let language = tree_sitter_c::language();
+    let constants = codegen::constants(&language);
+
+    // The constant holds the ID nodes of the kind usually report, and the
+    // `_IDS` slice every ID
+    let function_definition = language.id_for_node_kind("function_definition", true);
+    assert!(constants.contains(&format!(
+        "pub const FUNCTION_DEFINITION: u16 = {function_definition};"
+    )));
+    assert!(constants.contains(&format!(
+        "pub const FUNCTION_DEFINITION_IDS: &[u16] = &[{function_definition}, "
+    )));
+    assert!(!constants.contains("TRANSLATION_UNIT_IDS"));
+}
+
+#[test]
+fn test_write_constants() {
+    let language = tree_sitter_c::language();
+    let path = std::env::temp_dir().join(format!("kinds-{}.rs", std::process::id()));
+
+    codegen::write_constants(&language, &path).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        codegen::constants(&language)
+    );
+
+    // An unchanged file is not rewritten
+    let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+    codegen::write_constants(&language, &path).unwrap();
+    assert_eq!(
+        std::fs::metadata(&path).unwrap().modified().unwrap(),
+        modified
+    );
+
+    std::fs::remove_file(&path).unwrap();
+}
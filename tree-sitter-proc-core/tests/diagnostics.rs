@@ -0,0 +1,160 @@
+// Tests for the error messages, against the tree-sitter-c grammar
+use tree_sitter_proc_core::diagnostics::{
+    field_not_in_kind, field_not_in_kinds, kind_not_reachable, unknown_field, unknown_keyword,
+    unknown_kind,
+};
+use tree_sitter_proc_core::LangName;
+
+const C: LangName = LangName::new("tree-sitter-c").with_node_types(tree_sitter_c::NODE_TYPES);
+
+/// Returns the "did you mean" clause of `message`, if it has one.
+fn suggestion(message: &str) -> Option<&str> {
+    message
+        .find("; did you mean")
+        .map(|start| &message[start + 2..])
+}
+
+#[test]
+fn test_suggestions() {
+    let language = tree_sitter_c::language();
+
+    let message = unknown_kind(&language, C, "if_statment");
+    assert_eq!(
+        message,
+        "\"if_statment\" is not a valid node kind in the tree-sitter-c grammar; \
+         did you mean one of \"if_statement\", \"do_statement\"?"
+    );
+
+    let message = unknown_keyword(&language, C, "strct");
+    assert_eq!(suggestion(&message), Some("did you mean \"struct\"?"));
+
+    let message = unknown_field(&language, C, "bdy");
+    assert_eq!(suggestion(&message), Some("did you mean \"body\"?"));
+
+    // Named kinds aren't suggested for keywords, nor keywords for kinds
+    assert_eq!(suggestion(&unknown_kind(&language, C, "whlie")), None);
+    assert_eq!(
+        suggestion(&unknown_keyword(&language, C, "declarator")),
+        None
+    );
+
+    // Unrelated names get no suggestion
+    assert_eq!(suggestion(&unknown_kind(&language, C, "xyzzy")), None);
+    assert_eq!(suggestion(&unknown_field(&language, C, "primitive")), None);
+}
+
+#[test]
+fn test_suggestion_ranking() {
+    let suggest = |requested: &str, candidates: &[&str]| {
+        let message = field_not_in_kinds(C, &["call_expression"], requested, candidates);
+        suggestion(&message).map(str::to_string)
+    };
+
+    // Transposed characters count as one edit, and a deletion plus a
+    // transposition is too far for a five-character name
+    assert_eq!(
+        suggest("whlie", &["while", "wile"]).as_deref(),
+        Some("did you mean \"while\"?")
+    );
+
+    // Candidates are ordered by distance, then by name, listed once and
+    // limited to three
+    assert_eq!(
+        suggest(
+            "argument",
+            &[
+                "arguments",
+                "argument_",
+                "xargument",
+                "argumnt",
+                "argumnet",
+                "arguments"
+            ]
+        )
+        .as_deref(),
+        Some("did you mean one of \"argument_\", \"arguments\", \"argumnet\"?")
+    );
+
+    // The distance allowed grows with the length of the name: one edit for up
+    // to five characters, two for six to eight
+    assert_eq!(
+        suggest("bdy", &["body"]).as_deref(),
+        Some("did you mean \"body\"?")
+    );
+    assert_eq!(suggest("bd", &["body"]), None);
+    assert_eq!(
+        suggest("valeus", &["values"]).as_deref(),
+        Some("did you mean \"values\"?")
+    );
+    assert_eq!(suggest("vlaue", &["values"]), None);
+    assert_eq!(
+        suggest("dclartor", &["declarator"]).as_deref(),
+        Some("did you mean \"declarator\"?")
+    );
+
+    // Names compare by character, not by byte
+    assert_eq!(
+        suggest("naïve", &["naive"]).as_deref(),
+        Some("did you mean \"naive\"?")
+    );
+    assert_eq!(suggest("body", &[]), None);
+}
+
+#[test]
+fn test_field_not_in_kind() {
+    let message = field_not_in_kind(C, "if_statement", "then", &["condition", "consequence"]);
+    assert_eq!(
+        message,
+        "\"if_statement\" nodes have no \"then\" field in the tree-sitter-c grammar; \
+         its fields are \"condition\", \"consequence\""
+    );
+
+    let message = field_not_in_kind(C, "identifier", "name", &[]);
+    assert_eq!(
+        message,
+        "\"identifier\" nodes have no \"name\" field in the tree-sitter-c grammar; \
+         \"identifier\" has no fields"
+    );
+}
+
+#[test]
+fn test_field_not_in_kinds() {
+    let message = field_not_in_kinds(
+        C,
+        &["pointer_declarator", "function_declarator"],
+        "declaratr",
+        &["declarator", "parameters"],
+    );
+    assert_eq!(
+        message,
+        "no node that can occur here has a \"declaratr\" field in the tree-sitter-c grammar; \
+         the nodes that can are \"pointer_declarator\", \"function_declarator\"; \
+         did you mean \"declarator\"?"
+    );
+
+    // Long lists of kinds are cut short
+    let kinds = ["a", "b", "c", "d", "e", "f", "g", "h"];
+    let message = field_not_in_kinds(C, &kinds, "name", &[]);
+    assert!(
+        message.ends_with(
+            "the nodes that can are \"a\", \"b\", \"c\", \"d\", \"e\", \"f\", and 2 more"
+        ),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_kind_not_reachable() {
+    let message = kind_not_reachable(C, "comment", &["identifier", "parameter_declaration"]);
+    assert_eq!(
+        message,
+        "\"comment\" nodes can't occur here in the tree-sitter-c grammar; \
+         the nodes that can are \"identifier\", \"parameter_declaration\""
+    );
+
+    let message = kind_not_reachable(C, "identifier", &[]);
+    assert_eq!(
+        message,
+        "\"identifier\" nodes can't occur here in the tree-sitter-c grammar; no named node can"
+    );
+}
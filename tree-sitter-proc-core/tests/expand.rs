@@ -0,0 +1,126 @@
+// Tests for the expansions, against the tree-sitter-c grammar
+use proc_macro2::TokenStream;
+use quote::quote;
+use tree_sitter_proc_core::{
+    expand_field, expand_field_id, expand_kind, expand_kinds, expand_kw, expand_kws,
+    expand_supertype, LangName,
+};
+
+const C: LangName = LangName::new("tree-sitter-c").with_node_types(tree_sitter_c::NODE_TYPES);
+
+/// Returns the message of the `compile_error!` an expansion consists of.
+fn error_message(expansion: TokenStream) -> String {
+    let expansion = expansion.to_string();
+    let start = expansion
+        .find("compile_error")
+        .unwrap_or_else(|| panic!("`{expansion}` is not a compile error"));
+    expansion[start..].to_string()
+}
+
+#[test]
+fn test_expand_kind_and_kw() {
+    let language = tree_sitter_c::language();
+
+    let if_statement_id = language.id_for_node_kind("if_statement", true);
+    let expansion = expand_kind(&language, quote!("if_statement"), C);
+    assert_eq!(expansion.to_string(), quote!(#if_statement_id).to_string());
+
+    let if_keyword_id = language.id_for_node_kind("if", false);
+    let expansion = expand_kw(&language, quote!("if"), C);
+    assert_eq!(expansion.to_string(), quote!(#if_keyword_id).to_string());
+
+    // Aliased kinds expand to the ID `Node::kind_id` reports
+    let function_definition_id = language.id_for_node_kind("function_definition", true);
+    let expansion = expand_kind(&language, quote!("function_definition"), C);
+    assert_eq!(
+        expansion.to_string(),
+        quote!(#function_definition_id).to_string()
+    );
+
+    let expansion = expand_kinds(&language, quote!("if_statement", "while_statement"), C);
+    let while_statement_id = language.id_for_node_kind("while_statement", true);
+    assert_eq!(
+        expansion.to_string(),
+        quote!(| #if_statement_id | #while_statement_id).to_string()
+    );
+
+    let expansion = expand_kws(&language, quote!("if", "while"), C);
+    let while_keyword_id = language.id_for_node_kind("while", false);
+    assert_eq!(
+        expansion.to_string(),
+        quote!(| #if_keyword_id | #while_keyword_id).to_string()
+    );
+}
+
+#[test]
+fn test_expand_field() {
+    let language = tree_sitter_c::language();
+    let body_id = language.field_id_for_name("body").unwrap();
+
+    let expansion = expand_field_id(&language, quote!("body"), C);
+    assert_eq!(expansion.to_string(), quote!(#body_id).to_string());
+
+    let expansion = expand_field(&language, quote!("function_definition", "body"), C);
+    assert!(expansion.to_string().contains("NonZeroU16"));
+    assert!(!expansion.to_string().contains("compile_error"));
+}
+
+#[test]
+fn test_expand_supertype() {
+    let language = tree_sitter_c::language();
+    let expansion = expand_supertype(&language, quote!("_expression"), C).to_string();
+
+    let cast_expression_id = language.id_for_node_kind("cast_expression", true);
+    assert!(expansion
+        .split('|')
+        .any(|id| id.trim() == format!("{cast_expression_id}u16")));
+}
+
+#[test]
+fn test_error_messages() {
+    let language = tree_sitter_c::language();
+
+    let message = error_message(expand_kind(&language, quote!("if"), C));
+    assert!(
+        message.contains("use `kw!(\\\"if\\\")` instead"),
+        "{message}"
+    );
+
+    // Suggested invocations select the grammar when the macros take it
+    let message = error_message(expand_kind(&language, quote!("if"), C.with_argument("c")));
+    assert!(
+        message.contains("use `kw!(c, \\\"if\\\")` instead"),
+        "{message}"
+    );
+
+    let message = error_message(expand_kind(&language, quote!("_expression"), C));
+    assert!(message.contains("it is a supertype"), "{message}");
+
+    let message = error_message(expand_kw(&language, quote!("whlie"), C));
+    assert!(message.contains("did you mean \\\"while\\\"?"), "{message}");
+
+    let message = error_message(expand_field(&language, quote!("if_statement", "body"), C));
+    assert!(message.contains("its fields are"), "{message}");
+
+    let message = error_message(expand_kinds(&language, quote!(), C));
+    assert!(
+        message.contains("Expected at least one node kind"),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_without_node_types() {
+    let language = tree_sitter_c::language();
+    let name = LangName::new("tree-sitter-c");
+
+    // The checks that don't need node-types.json still work
+    let expansion = expand_kind(&language, quote!("_expression"), name);
+    assert!(!error_message(expansion).contains("supertype"));
+    assert!(!expand_field(&language, quote!("body"), name)
+        .to_string()
+        .contains("compile_error"));
+
+    let message = error_message(expand_supertype(&language, quote!("_expression"), name));
+    assert!(message.contains("needs the node-types.json"), "{message}");
+}
@@ -0,0 +1,143 @@
+//! Locating a grammar and reading what the generated crate needs from it.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Deserialize;
+
+/// Where the grammar comes from.
+pub(crate) enum Source {
+    /// A grammar crate published on crates.io.
+    Crate { name: String, version: String },
+    /// A local checkout of a grammar, with its Rust bindings.
+    Directory(PathBuf),
+}
+
+/// A grammar's crate and its `node-types.json`.
+pub(crate) struct Grammar {
+    /// The name of the grammar's crate, such as `tree-sitter-c`.
+    pub crate_name: String,
+    pub version: String,
+    pub repository: Option<String>,
+    /// The directory of a local checkout, which the generated crate depends on
+    /// by path.
+    pub local: Option<PathBuf>,
+    /// Where the generated crate goes unless `--out` is given.
+    pub default_out: PathBuf,
+    pub node_types: String,
+    /// The grammar's `grammar.json`, which not every published crate ships.
+    pub grammar_json: Option<String>,
+}
+
+/// The parts of a grammar's `Cargo.toml` the generator reads.
+#[derive(Deserialize)]
+struct Manifest {
+    package: Package,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    name: String,
+    version: String,
+    repository: Option<String>,
+}
+
+/// Finds the grammar of `source` and reads its manifest and `node-types.json`.
+pub(crate) fn load(source: &Source) -> Result<Grammar, String> {
+    match source {
+        Source::Crate { name, version } => {
+            let directory = match registry_source(name, version) {
+                Some(directory) => directory,
+                None => {
+                    fetch(name, version)?;
+                    registry_source(name, version).ok_or_else(|| {
+                        format!("cargo fetched {name} {version}, but its source wasn't found")
+                    })?
+                }
+            };
+            let current = env::current_dir()
+                .map_err(|error| format!("failed to read the current directory: {error}"))?;
+            read(&directory, None, current.join("bindings/rust/macros"))
+        }
+        Source::Directory(directory) => {
+            let directory = fs::canonicalize(directory)
+                .map_err(|error| format!("failed to read {}: {error}", directory.display()))?;
+            let default_out = directory.join("bindings/rust/macros");
+            read(&directory, Some(directory.clone()), default_out)
+        }
+    }
+}
+
+fn read(directory: &Path, local: Option<PathBuf>, default_out: PathBuf) -> Result<Grammar, String> {
+    let manifest_path = directory.join("Cargo.toml");
+    let manifest: Manifest = toml::from_str(&read_file(&manifest_path)?).map_err(|error| {
+        format!(
+            "{} is not a valid crate manifest: {error}",
+            manifest_path.display()
+        )
+    })?;
+    let node_types = read_file(&directory.join("src/node-types.json"))?;
+    let grammar_json_path = directory.join("src/grammar.json");
+    let grammar_json = if grammar_json_path.exists() {
+        Some(read_file(&grammar_json_path)?)
+    } else {
+        None
+    };
+
+    Ok(Grammar {
+        crate_name: manifest.package.name,
+        version: manifest.package.version,
+        repository: manifest.package.repository,
+        local,
+        default_out,
+        node_types,
+        grammar_json,
+    })
+}
+
+/// Returns the directory cargo unpacked `name` at `version` into, if it has
+/// been downloaded before.
+fn registry_source(name: &str, version: &str) -> Option<PathBuf> {
+    let cargo_home = env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".cargo")))?;
+    let registries = fs::read_dir(cargo_home.join("registry/src")).ok()?;
+    registries
+        .filter_map(Result::ok)
+        .map(|registry| registry.path().join(format!("{name}-{version}")))
+        .find(|directory| directory.join("Cargo.toml").is_file())
+}
+
+/// Downloads `name` at `version` into cargo's registry cache, through a
+/// temporary crate that depends on it.
+fn fetch(name: &str, version: &str) -> Result<(), String> {
+    let directory = env::temp_dir().join(format!("tree-sitter-proc-gen-{}", std::process::id()));
+    let manifest = format!(
+        "[package]\nname = \"fetch\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\n\
+         [dependencies]\n{name} = \"={version}\"\n\n[workspace]\n"
+    );
+    fs::create_dir_all(directory.join("src"))
+        .and_then(|()| fs::write(directory.join("Cargo.toml"), manifest))
+        .and_then(|()| fs::write(directory.join("src/lib.rs"), ""))
+        .map_err(|error| format!("failed to set up {}: {error}", directory.display()))?;
+
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let status = Command::new(cargo)
+        .arg("fetch")
+        .arg("--manifest-path")
+        .arg(directory.join("Cargo.toml"))
+        .status();
+    let _ = fs::remove_dir_all(&directory);
+
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(_) => Err(format!("cargo failed to fetch {name} {version}")),
+        Err(error) => Err(format!("failed to run cargo: {error}")),
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|error| format!("failed to read {}: {error}", path.display()))
+}
@@ -0,0 +1,137 @@
+//! Generates a proc macro crate like tree-sitter-c-proc for any tree-sitter
+//! grammar.
+//!
+//! The crate is rendered from the `template` directory of this repository,
+//! with the grammar's crate name and version filled in, tests that use real
+//! node kinds, keywords and fields of the grammar, and a README.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+mod grammar;
+mod render;
+mod samples;
+
+use grammar::Source;
+
+const USAGE: &str = "\
+Usage:
+    tree-sitter-proc-gen <grammar-crate> <version> [--out <dir>] [--force]
+    tree-sitter-proc-gen --grammar-dir <dir> [--out <dir>] [--force]
+
+Writes a proc macro crate for a tree-sitter grammar, by default to
+bindings/rust/macros in the current directory, or in the grammar directory.
+
+Options:
+    --grammar-dir <dir>  Read the grammar from a local checkout instead of a
+                         published crate
+    --out <dir>          Write the crate to <dir>
+    --force              Overwrite the files of an existing crate
+    -h, --help           Print this message";
+
+/// The parsed command line.
+struct Options {
+    source: Source,
+    out: Option<PathBuf>,
+    force: bool,
+}
+
+fn main() -> ExitCode {
+    let arguments: Vec<String> = env::args().skip(1).collect();
+    if arguments
+        .iter()
+        .any(|argument| argument == "-h" || argument == "--help")
+    {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    match parse_options(arguments).and_then(|options| run(&options)) {
+        Ok(out) => {
+            println!("Wrote {}", out.display());
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("error: {message}\n\n{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_options(arguments: Vec<String>) -> Result<Options, String> {
+    let mut positional = Vec::new();
+    let mut grammar_dir = None;
+    let mut out = None;
+    let mut force = false;
+
+    let mut arguments = arguments.into_iter();
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--grammar-dir" => grammar_dir = Some(option_value(&mut arguments, &argument)?),
+            "--out" => out = Some(option_value(&mut arguments, &argument)?),
+            "--force" => force = true,
+            _ if argument.starts_with('-') => return Err(format!("unknown option `{argument}`")),
+            _ => positional.push(argument),
+        }
+    }
+
+    let source = match (grammar_dir, positional.as_slice()) {
+        (Some(directory), []) => Source::Directory(directory),
+        (None, [name, version]) => Source::Crate {
+            name: name.clone(),
+            version: version.clone(),
+        },
+        (Some(_), _) => return Err("`--grammar-dir` takes no grammar crate".to_string()),
+        (None, _) => {
+            return Err("expected a grammar crate and its version, or `--grammar-dir`".to_string())
+        }
+    };
+
+    Ok(Options { source, out, force })
+}
+
+fn option_value(
+    arguments: &mut impl Iterator<Item = String>,
+    option: &str,
+) -> Result<PathBuf, String> {
+    arguments
+        .next()
+        .map(PathBuf::from)
+        .ok_or_else(|| format!("`{option}` needs a directory"))
+}
+
+/// Generates the crate and returns the directory it was written to.
+fn run(options: &Options) -> Result<PathBuf, String> {
+    let grammar = grammar::load(&options.source)?;
+    let samples = samples::pick(&grammar.node_types, grammar.grammar_json.as_deref())?;
+
+    let out = match &options.out {
+        Some(out) => out.clone(),
+        None => grammar.default_out.clone(),
+    };
+    let files = render::render(&grammar, &samples, &out);
+
+    if !options.force {
+        if let Some((existing, _)) = files.iter().find(|(path, _)| out.join(path).exists()) {
+            return Err(format!(
+                "{} already exists; pass `--force` to overwrite it",
+                out.join(existing).display()
+            ));
+        }
+    }
+    for (path, contents) in &files {
+        write(&out.join(path), contents)?;
+    }
+    Ok(out)
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("failed to create {}: {error}", parent.display()))?;
+    }
+    fs::write(path, contents)
+        .map_err(|error| format!("failed to write {}: {error}", path.display()))
+}
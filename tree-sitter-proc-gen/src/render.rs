@@ -0,0 +1,266 @@
+//! Rendering the files of the generated crate.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use crate::grammar::Grammar;
+use crate::samples::{self, Samples};
+
+const CARGO_TOML: &str = include_str!("../../template/Cargo.toml");
+const LIB_RS: &str = include_str!("../../template/src/lib.rs");
+
+/// Returns the path, relative to the crate directory `out`, and the contents
+/// of every file of the generated crate.
+pub(crate) fn render(grammar: &Grammar, samples: &Samples, out: &Path) -> Vec<(PathBuf, String)> {
+    vec![
+        (PathBuf::from("Cargo.toml"), cargo_toml(grammar, out)),
+        (PathBuf::from("src/lib.rs"), fill(LIB_RS, grammar)),
+        (PathBuf::from("tests/macros.rs"), tests(grammar, samples)),
+        (PathBuf::from("README.md"), readme(grammar, samples)),
+    ]
+}
+
+/// Returns the language part of the grammar's crate name, such as the `c` in
+/// `tree-sitter-c`.
+fn language_name(grammar: &Grammar) -> &str {
+    grammar
+        .crate_name
+        .strip_prefix("tree-sitter-")
+        .unwrap_or(&grammar.crate_name)
+}
+
+/// Returns the name of the generated crate, such as `tree-sitter-c-proc`.
+fn proc_crate_name(grammar: &Grammar) -> String {
+    format!("{}-proc", grammar.crate_name)
+}
+
+fn ident(crate_name: &str) -> String {
+    crate_name.replace('-', "_")
+}
+
+/// Replaces the `LANG` placeholders of a template file.
+fn fill(template: &str, grammar: &Grammar) -> String {
+    template
+        .replace("tree-sitter-LANG", &grammar.crate_name)
+        .replace("tree_sitter_LANG", &ident(&grammar.crate_name))
+        .replace("LANG", language_name(grammar))
+}
+
+/// Replaces `from` in `text`, which the template must contain.
+fn replace_once(text: &str, from: &str, to: &str) -> String {
+    assert!(
+        text.contains(from),
+        "template/Cargo.toml no longer contains `{from}`"
+    );
+    text.replacen(from, to, 1)
+}
+
+fn cargo_toml(grammar: &Grammar, out: &Path) -> String {
+    // A local grammar is a path dependency, relative when the crate is in the
+    // usual place in the grammar's bindings
+    let dependency = match &grammar.local {
+        Some(_) if out == grammar.default_out => "{ path = \"../../..\" }".to_string(),
+        Some(directory) => format!("{{ path = {:?} }}", directory.display().to_string()),
+        None => format!("{:?}", grammar.version),
+    };
+
+    let mut manifest = replace_once(
+        CARGO_TOML,
+        "tree-sitter-LANG = \"0.1.0\"",
+        &format!("{} = {dependency}", grammar.crate_name),
+    );
+    manifest = replace_once(
+        &manifest,
+        "version = \"0.1.0\"",
+        &format!("version = {:?}", grammar.version),
+    );
+    manifest = replace_once(
+        &manifest,
+        "path = \"../tree-sitter-proc-core\"",
+        &format!("path = {:?}", core_dir().display().to_string()),
+    );
+    if let Some(repository) = &grammar.repository {
+        manifest = replace_once(
+            &manifest,
+            "\"https://github.com/tree-sitter/tree-sitter-LANG\"",
+            &format!("{repository:?}"),
+        );
+    }
+    fill(&manifest, grammar)
+}
+
+/// Returns the directory of the tree-sitter-proc-core the generator was built
+/// with, which the generated crate depends on by path until it is published.
+fn core_dir() -> PathBuf {
+    let core_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../tree-sitter-proc-core");
+    core_dir.canonicalize().unwrap_or(core_dir)
+}
+
+/// A category of names the generated tests check.
+struct Category<'a> {
+    test: &'a str,
+    description: &'a str,
+    names: &'a [String],
+    /// The expression for the ID the grammar reports at runtime.
+    expected: fn(&str) -> String,
+    /// The conversion of the macro's value to the type of `expected`.
+    get: &'a str,
+}
+
+fn tests(grammar: &Grammar, samples: &Samples) -> String {
+    let proc_crate = proc_crate_name(grammar);
+    let grammar_crate = ident(&grammar.crate_name);
+
+    let categories = [
+        Category {
+            test: "kind",
+            description: "node kinds",
+            names: &samples.kinds,
+            expected: |name| format!("language.id_for_node_kind({name:?}, true)"),
+            get: "",
+        },
+        Category {
+            test: "kw",
+            description: "keywords",
+            names: &samples.keywords,
+            expected: |name| format!("language.id_for_node_kind({name:?}, false)"),
+            get: "",
+        },
+        Category {
+            test: "field",
+            description: "fields",
+            names: &samples.fields,
+            expected: |name| format!("language.field_id_for_name({name:?}).unwrap()"),
+            get: ".get()",
+        },
+    ];
+
+    let mut macros: Vec<&str> = categories
+        .iter()
+        .filter(|category| !category.names.is_empty())
+        .map(|category| category.test)
+        .collect();
+    macros.sort_unstable();
+
+    let mut tests = format!(
+        "// Tests for the {proc_crate} macros, generated by tree-sitter-proc-gen\n\
+         use {}::{{{}}};\n",
+        ident(&proc_crate),
+        macros.join(", ")
+    );
+
+    for category in categories
+        .iter()
+        .filter(|category| !category.names.is_empty())
+    {
+        let Category {
+            test,
+            description,
+            names,
+            expected,
+            get,
+        } = category;
+
+        write!(
+            tests,
+            "\n#[test]\n\
+             fn test_{test}_macro() {{\n    \
+             let language = {grammar_crate}::language();\n\n    \
+             // Test with valid {description}, against the IDs the grammar reports at runtime\n"
+        )
+        .unwrap();
+        for name in names.iter() {
+            let variable = samples::variable(name);
+            let invocation = format!("{test}!({name:?})");
+            let expected = expected(name);
+            write!(
+                tests,
+                "    let {variable} = {invocation};\n    \
+                 assert_eq!({variable}{get}, {expected});\n"
+            )
+            .unwrap();
+        }
+
+        let variables: Vec<String> = names.iter().map(|name| samples::variable(name)).collect();
+        if variables.len() > 1 {
+            write!(
+                tests,
+                "\n    // Verify that different {description} have different IDs\n"
+            )
+            .unwrap();
+            for (index, first) in variables.iter().enumerate() {
+                for second in &variables[index + 1..] {
+                    write!(
+                        tests,
+                        "    assert_ne!(\n        \
+                         {first}, {second},\n        \
+                         \"Different {description} should have different IDs\"\n    \
+                         );\n"
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        write!(
+            tests,
+            "\n    // Note: Invalid {description} cause compile errors, so we can't test them here\n}}\n"
+        )
+        .unwrap();
+    }
+    tests
+}
+
+fn readme(grammar: &Grammar, samples: &Samples) -> String {
+    let proc_crate = proc_crate_name(grammar);
+    let crate_name = &grammar.crate_name;
+    let version = &grammar.version;
+
+    let mut arms = String::new();
+    for kind in &samples.kinds {
+        writeln!(arms, "    kind!({kind:?}) => {{}},").unwrap();
+    }
+    if let Some(keyword) = samples.keywords.first() {
+        writeln!(arms, "    kw!({keyword:?}) => {{}},").unwrap();
+    }
+    let field = samples.fields.first().map_or(String::new(), |field| {
+        format!("\nlet child = node.child_by_field_id(field!({field:?}).get());\n")
+    });
+
+    format!(
+        "# {proc_crate}\n\
+         \n\
+         Proc macros for compile-time access to {crate_name} node kinds, keywords, and fields.\n\
+         \n\
+         This crate was generated by `tree-sitter-proc-gen`.\n\
+         \n\
+         ## Installation\n\
+         \n\
+         ```toml\n\
+         [dependencies]\n\
+         {crate_name} = \"{version}\"\n\
+         {proc_crate} = \"{version}\"\n\
+         ```\n\
+         \n\
+         ## Usage\n\
+         \n\
+         ```rust\n\
+         use {}::{{field, kind, kw}};\n\
+         \n\
+         match node.kind_id() {{\n\
+         {arms}    _ => {{}},\n\
+         }}\n\
+         {field}```\n\
+         \n\
+         `kind!` returns the ID of a named node kind, `kw!` the ID of a keyword or other anonymous token, \
+         and `field!` the ID of a field as a `NonZeroU16`. Names that are not part of the {crate_name} \
+         grammar are compile errors.\n\
+         \n\
+         ## Testing\n\
+         \n\
+         ```bash\n\
+         cargo test\n\
+         ```\n",
+        ident(&proc_crate)
+    )
+}
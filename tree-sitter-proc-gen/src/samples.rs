@@ -0,0 +1,154 @@
+//! Picking the node kinds, keywords and fields the generated tests use.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+/// How many names of each category the generated tests check.
+const COUNT: usize = 3;
+
+/// One entry of `node-types.json`.
+#[derive(Deserialize)]
+struct NodeType {
+    #[serde(rename = "type")]
+    kind: String,
+    named: bool,
+    #[serde(default)]
+    fields: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    subtypes: Vec<serde_json::Value>,
+}
+
+/// Names that exist in the grammar, for the generated tests and README.
+pub(crate) struct Samples {
+    pub kinds: Vec<String>,
+    pub keywords: Vec<String>,
+    pub fields: Vec<String>,
+}
+
+/// Picks a few node kinds, keywords and fields from `node-types.json`.
+///
+/// Every name gets a local variable in the generated tests, so names that
+/// would give two variables the same name are skipped. So are the targets of
+/// aliases in `grammar_json`, which can belong to several symbol IDs.
+pub(crate) fn pick(node_types_json: &str, grammar_json: Option<&str>) -> Result<Samples, String> {
+    let node_types: Vec<NodeType> = serde_json::from_str(node_types_json)
+        .map_err(|error| format!("node-types.json is not valid: {error}"))?;
+    let aliases = match grammar_json {
+        Some(grammar_json) => aliases(grammar_json)?,
+        None => Vec::new(),
+    };
+    let single_id = |node_type: &&NodeType| {
+        !aliases
+            .iter()
+            .any(|(value, named)| *value == node_type.kind && *named == node_type.named)
+    };
+
+    // Supertypes and hidden rules never appear in a tree
+    let kinds = distinct(
+        node_types
+            .iter()
+            .filter(|node_type| {
+                node_type.named && node_type.subtypes.is_empty() && !node_type.kind.starts_with('_')
+            })
+            .filter(single_id)
+            .map(|node_type| node_type.kind.as_str()),
+    );
+
+    // Keywords are the anonymous tokens that look like identifiers
+    let keywords = distinct(
+        node_types
+            .iter()
+            .filter(|node_type| {
+                !node_type.named
+                    && node_type
+                        .kind
+                        .starts_with(|character: char| character.is_ascii_alphabetic())
+                    && node_type
+                        .kind
+                        .chars()
+                        .all(|character| character.is_ascii_alphanumeric() || character == '_')
+            })
+            .filter(single_id)
+            .map(|node_type| node_type.kind.as_str()),
+    );
+
+    let mut field_names: Vec<&str> = node_types
+        .iter()
+        .flat_map(|node_type| node_type.fields.keys().map(String::as_str))
+        .collect();
+    field_names.sort_unstable();
+    field_names.dedup();
+    let fields = distinct(field_names.into_iter());
+
+    if kinds.is_empty() {
+        return Err("node-types.json has no named node kinds".to_string());
+    }
+    Ok(Samples {
+        kinds,
+        keywords,
+        fields,
+    })
+}
+
+/// Returns the name and named-ness of every alias in `grammar.json`.
+fn aliases(grammar_json: &str) -> Result<Vec<(String, bool)>, String> {
+    let grammar: serde_json::Value = serde_json::from_str(grammar_json)
+        .map_err(|error| format!("grammar.json is not valid: {error}"))?;
+
+    // Rules nest arbitrarily deep, so walk them with a stack
+    let mut aliases = Vec::new();
+    let mut stack = vec![&grammar];
+    while let Some(value) = stack.pop() {
+        match value {
+            serde_json::Value::Object(object) => {
+                if object.get("type").and_then(serde_json::Value::as_str) == Some("ALIAS") {
+                    let name = object.get("value").and_then(serde_json::Value::as_str);
+                    let named = object.get("named").and_then(serde_json::Value::as_bool);
+                    if let (Some(name), Some(named)) = (name, named) {
+                        aliases.push((name.to_string(), named));
+                    }
+                }
+                stack.extend(object.values());
+            }
+            serde_json::Value::Array(array) => stack.extend(array),
+            _ => {}
+        }
+    }
+    Ok(aliases)
+}
+
+/// Returns the first `COUNT` names whose local variables don't collide.
+fn distinct<'a>(names: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut picked: Vec<String> = Vec::new();
+    for name in names {
+        if picked.len() == COUNT {
+            break;
+        }
+        if !picked.iter().any(|other| variable(other) == variable(name)) {
+            picked.push(name.to_string());
+        }
+    }
+    picked
+}
+
+/// Returns the local variable the generated tests store the ID of `name` in,
+/// such as `if_statement_id`.
+pub(crate) fn variable(name: &str) -> String {
+    let base: String = name
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() {
+                character.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let base = base.trim_matches('_');
+    if base.is_empty() || base.starts_with(|character: char| character.is_ascii_digit()) {
+        format!("symbol_{base}_id")
+    } else {
+        format!("{base}_id")
+    }
+}
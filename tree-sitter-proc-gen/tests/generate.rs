@@ -0,0 +1,191 @@
+// Tests for tree-sitter-proc-gen, run against a copy of the tree-sitter-c grammar
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Creates a grammar checkout with the manifest and `node-types.json` the
+/// generator reads.
+fn grammar_dir(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!(
+        "tree-sitter-proc-gen-test-{name}-{}",
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(directory.join("src")).unwrap();
+    fs::write(
+        directory.join("Cargo.toml"),
+        "[package]\n\
+         name = \"tree-sitter-c\"\n\
+         version = \"0.20.8\"\n\
+         repository = \"https://github.com/tree-sitter/tree-sitter-c\"\n",
+    )
+    .unwrap();
+    fs::write(
+        directory.join("src/node-types.json"),
+        tree_sitter_c::NODE_TYPES,
+    )
+    .unwrap();
+    directory
+}
+
+fn generate(arguments: &[&Path]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_tree-sitter-proc-gen"))
+        .args(arguments)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_generate_from_grammar_dir() {
+    let directory = grammar_dir("generate");
+    let output = generate(&[Path::new("--grammar-dir"), &directory]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let out = directory.join("bindings/rust/macros");
+    let manifest = fs::read_to_string(out.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("name = \"tree-sitter-c-proc\""));
+    assert!(manifest.contains("version = \"0.20.8\""));
+    assert!(manifest.contains("tree-sitter-c = { path = \"../../..\" }"));
+    assert!(manifest.contains("tree-sitter-proc-core = { path = "));
+    assert!(!manifest.contains("../tree-sitter-proc-core"));
+    assert!(!manifest.contains("LANG"));
+
+    let lib = fs::read_to_string(out.join("src/lib.rs")).unwrap();
+    assert!(lib.contains("tree_sitter_c::language()"));
+    assert!(!lib.contains("LANG"));
+
+    // The tests use names that are really part of the grammar
+    let tests = fs::read_to_string(out.join("tests/macros.rs")).unwrap();
+    assert!(tests.contains("use tree_sitter_c_proc::{field, kind, kw};"));
+    assert!(tests.contains("kind!(\"abstract_array_declarator\")"));
+    assert!(tests.contains("kw!(\"NULL\")"));
+    assert!(tests.contains("field!(\"alternative\")"));
+
+    let readme = fs::read_to_string(out.join("README.md")).unwrap();
+    assert!(readme.starts_with("# tree-sitter-c-proc\n"));
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_samples_skip_aliases() {
+    // Aliased names can belong to several symbol IDs, so the tests don't use
+    // them
+    let directory = grammar_dir("aliases");
+    fs::write(
+        directory.join("src/grammar.json"),
+        r#"{
+            "name": "c",
+            "rules": {
+                "translation_unit": {
+                    "type": "SEQ",
+                    "members": [
+                        {"type": "ALIAS", "content": {"type": "SYMBOL", "name": "_a"},
+                         "named": true, "value": "abstract_array_declarator"},
+                        {"type": "ALIAS", "content": {"type": "STRING", "value": "null"},
+                         "named": false, "value": "NULL"}
+                    ]
+                }
+            }
+        }"#,
+    )
+    .unwrap();
+    assert!(generate(&[Path::new("--grammar-dir"), &directory])
+        .status
+        .success());
+
+    let tests = fs::read_to_string(directory.join("bindings/rust/macros/tests/macros.rs")).unwrap();
+    assert!(!tests.contains("kind!(\"abstract_array_declarator\")"));
+    assert!(tests.contains("kind!(\"abstract_function_declarator\")"));
+    assert!(!tests.contains("kw!(\"NULL\")"));
+    assert!(tests.contains("kw!(\"alignof\")"));
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_generated_crate_builds() {
+    // Generate from the tree-sitter-c crate in cargo's registry, which the
+    // dev-dependency downloaded, and run the generated tests against it
+    let directory = std::env::temp_dir().join(format!(
+        "tree-sitter-proc-gen-test-build-{}",
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&directory);
+    let out = directory.join("tree-sitter-c-proc");
+    let output = generate(&[
+        Path::new("tree-sitter-c"),
+        Path::new("0.20.8"),
+        Path::new("--out"),
+        &out,
+    ]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Everything the crate needs was fetched to build this one, and a target
+    // directory of its own is reused between runs
+    let output = Command::new(env!("CARGO"))
+        .args(["test", "--offline", "--manifest-path"])
+        .arg(out.join("Cargo.toml"))
+        .env(
+            "CARGO_TARGET_DIR",
+            Path::new(env!("CARGO_TARGET_TMPDIR")).join("generated"),
+        )
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn test_existing_crate_needs_force() {
+    let directory = grammar_dir("force");
+    let out = directory.join("macros");
+    assert!(generate(&[
+        Path::new("--grammar-dir"),
+        &directory,
+        Path::new("--out"),
+        &out
+    ])
+    .status
+    .success());
+
+    // An absolute path dependency, since the crate is not in the bindings
+    let manifest = fs::read_to_string(out.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains(&format!(
+        "{{ path = {:?} }}",
+        directory.display().to_string()
+    )));
+
+    let output = generate(&[
+        Path::new("--grammar-dir"),
+        &directory,
+        Path::new("--out"),
+        &out,
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("pass `--force` to overwrite it"));
+
+    let output = generate(&[
+        Path::new("--grammar-dir"),
+        &directory,
+        Path::new("--out"),
+        &out,
+        Path::new("--force"),
+    ]);
+    assert!(output.status.success());
+
+    fs::remove_dir_all(&directory).unwrap();
+}